@@ -2,26 +2,75 @@ use crate::{improper_attr_format, quote_path, read_path_item};
 
 use syn::{Ident, Lit, Meta, MetaNameValue, NestedMeta};
 
+/// A boolean expression over which components an entity has. `none(a, b)`
+/// matches when neither is present, while `not(a, b)` only rejects entities
+/// which have both.
+pub enum AspectExpr {
+    Has(Ident),
+    All(Vec<AspectExpr>),
+    Any(Vec<AspectExpr>),
+    None(Vec<AspectExpr>),
+    Not(Vec<AspectExpr>),
+}
+
+impl Default for AspectExpr {
+    fn default() -> Self {
+        AspectExpr::All(vec![])
+    }
+}
+
+impl AspectExpr {
+    fn push(&mut self, item: AspectExpr) {
+        match self {
+            AspectExpr::All(items)
+            | AspectExpr::Any(items)
+            | AspectExpr::None(items)
+            | AspectExpr::Not(items) => items.push(item),
+            AspectExpr::Has(_) => unreachable!(),
+        }
+    }
+
+    fn quote(&self) -> proc_macro2::TokenStream {
+        match self {
+            AspectExpr::Has(component) => {
+                quote! { components.#component.__matches(entity, signature) }
+            }
+            AspectExpr::All(items) => quote_all(items),
+            AspectExpr::Any(items) => quote_any(items),
+            AspectExpr::None(items) => {
+                let any = quote_any(items);
+                quote! { !#any }
+            }
+            AspectExpr::Not(items) => {
+                let all = quote_all(items);
+                quote! { !#all }
+            }
+        }
+    }
+}
+
+fn quote_all(items: &[AspectExpr]) -> proc_macro2::TokenStream {
+    let items = items.iter().map(AspectExpr::quote);
+    quote! { (true #(&& #items)*) }
+}
+
+fn quote_any(items: &[AspectExpr]) -> proc_macro2::TokenStream {
+    let items = items.iter().map(AspectExpr::quote);
+    quote! { (false #(|| #items)*) }
+}
+
 pub fn quote_aspect(
     ty: &Ident,
     cty: &proc_macro2::TokenStream,
-    all_filters: &[Ident],
-    none_filters: &[Ident],
+    aspect: &AspectExpr,
 ) -> proc_macro2::TokenStream {
+    let check = aspect.quote();
     quote! {
         impl ::conniecs::aspect::AspectFilter<#cty> for #ty {
             fn check<'a>(&self, entity: ::conniecs::EntityData<'a, #cty >, components: & #cty ) -> bool {
-                #(
-                    if !components.#all_filters.has(entity) {
-                        return false;
-                    }
-                )*
-                #(
-                    if components.#none_filters.has(entity) {
-                        return false;
-                    }
-                )*
-                true
+                let signature = entity.signature();
+                let _ = (entity, components, signature);
+                #check
             }
         }
     }
@@ -29,8 +78,7 @@ pub fn quote_aspect(
 
 pub fn impl_aspect(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
     let ty = &ast.ident;
-    let mut all_filters = vec![];
-    let mut none_filters = vec![];
+    let mut aspect = AspectExpr::default();
     let mut components_ty = None;
 
     for attr in &ast.attrs {
@@ -42,7 +90,7 @@ pub fn impl_aspect(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
                 components_ty = Some(word);
             }
             ("aspect", Meta::List(list)) => {
-                read_aspect(list.nested.iter(), &mut all_filters, &mut none_filters);
+                read_aspect(list.nested.iter(), &mut aspect);
             }
             _ => continue,
         }
@@ -53,17 +101,13 @@ pub fn impl_aspect(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
         None => quote_path("crate::Components"),
     };
 
-    quote_aspect(ty, &cty, &all_filters, &none_filters)
+    quote_aspect(ty, &cty, &aspect)
 }
 
-pub fn read_aspect_meta<'a>(
-    attr: &'a Meta,
-    all: &mut Vec<Ident>,
-    none: &mut Vec<Ident>,
-) -> Option<proc_macro2::TokenStream> {
+pub fn read_aspect_meta(attr: &Meta, aspect: &mut AspectExpr) -> Option<proc_macro2::TokenStream> {
     match attr {
         Meta::List(list) => {
-            read_aspect(list.nested.iter(), all, none);
+            read_aspect(list.nested.iter(), aspect);
             None
         }
         Meta::NameValue(MetaNameValue {
@@ -74,44 +118,43 @@ pub fn read_aspect_meta<'a>(
     }
 }
 
-pub fn read_aspect<'a>(
-    items: impl IntoIterator<Item = &'a NestedMeta>,
-    all: &mut Vec<Ident>,
-    none: &mut Vec<Ident>,
-) {
+/// Reads the items of an `#[aspect(...)]` attribute into `aspect`. At the top
+/// level every item must be one of the `all(...)`, `any(...)`, `none(...)` or
+/// `not(...)` groups, and they must all hold for an entity to match. Inside of
+/// a group, items may be component names or further nested groups.
+pub fn read_aspect<'a>(items: impl IntoIterator<Item = &'a NestedMeta>, aspect: &mut AspectExpr) {
     for item in items {
         let item = unwrap_meta(item);
-        let items = unwrap_list(item);
-        match item.name().to_string().as_str() {
-            "all" => {
-                for item in items {
-                    let item = unwrap_meta(item);
-                    let component = unwrap_word(item);
-                    all.push(component.clone());
-                }
-            }
-            "none" => {
-                for item in items {
-                    let item = unwrap_meta(item);
-                    let component = unwrap_word(item);
-                    none.push(component.clone());
-                }
-            }
+        match item {
+            Meta::List(_) => aspect.push(read_group(item)),
             _ => improper_format(),
         }
     }
 }
 
-fn unwrap_list<'a>(item: &'a Meta) -> impl Iterator<Item = &'a NestedMeta> {
-    match item {
-        Meta::List(list) => list.nested.iter(),
+fn read_group(group: &Meta) -> AspectExpr {
+    let mut expr = match group.name().to_string().as_str() {
+        "all" => AspectExpr::All(vec![]),
+        "any" => AspectExpr::Any(vec![]),
+        "none" => AspectExpr::None(vec![]),
+        "not" => AspectExpr::Not(vec![]),
         _ => improper_format(),
+    };
+
+    for item in unwrap_list(group) {
+        match unwrap_meta(item) {
+            Meta::Word(component) => expr.push(AspectExpr::Has(component.clone())),
+            item @ Meta::List(_) => expr.push(read_group(item)),
+            Meta::NameValue(_) => improper_format(),
+        }
     }
+
+    expr
 }
 
-fn unwrap_word(item: &Meta) -> &Ident {
+fn unwrap_list(item: &Meta) -> impl Iterator<Item = &NestedMeta> {
     match item {
-        Meta::Word(ident) => ident,
+        Meta::List(list) => list.nested.iter(),
         _ => improper_format(),
     }
 }
@@ -0,0 +1,111 @@
+use crate::{improper_attr_format, quote_path, read_path_item};
+
+use syn::{Data, Field, Fields, Ident};
+
+pub fn impl_bundle(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
+    if ast.generics != Default::default() {
+        panic!("There may not be generics attached to a Bundle struct");
+    }
+
+    let name = &ast.ident;
+    let fields = match &ast.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().collect(),
+            Fields::Unit => vec![],
+            Fields::Unnamed(_) => {
+                panic!("A Bundle may not be represented by a tuple struct.");
+            }
+        },
+        _ => panic!("A Bundle may only be represented by a struct."),
+    };
+
+    let mut components_ty = None;
+    for attr in &ast.attrs {
+        let meta = attr.parse_meta().unwrap();
+        if meta.name() == "components" {
+            let word = read_path_item(&meta, || improper_comp_format());
+            components_ty = Some(word);
+        }
+    }
+    let cty = match components_ty {
+        Some(ty) => quote_path(&ty),
+        None => quote_path("crate::Components"),
+    };
+
+    let field_names = fields
+        .iter()
+        .map(|field| field.ident.clone().unwrap())
+        .collect::<Vec<_>>();
+    let field_names = &field_names;
+    let list_names = fields
+        .iter()
+        .map(|field| list_name(field))
+        .collect::<Vec<_>>();
+    let list_names = &list_names;
+
+    quote! {
+        impl<M> ::conniecs::entity::EntityBuilder<#cty, M> for #name
+        where
+            M: ::conniecs::services::ServiceManager,
+        {
+            fn build<'a>(
+                self,
+                entity: ::conniecs::BuildData<'a, #cty>,
+                components: &mut #cty,
+                _: &mut M,
+            ) {
+                let _ = (entity, &components);
+                #(
+                    components.#list_names.add(entity, self.#field_names);
+                )*
+            }
+        }
+
+        impl<M> ::conniecs::entity::EntityModifier<#cty, M> for #name
+        where
+            M: ::conniecs::services::ServiceManager,
+        {
+            fn modify<'a>(
+                self,
+                entity: ::conniecs::ModifyData<'a, #cty>,
+                components: &mut #cty,
+                _: &mut M,
+            ) {
+                let _ = (entity, &components);
+                #(
+                    components.#list_names.set(entity, self.#field_names);
+                )*
+            }
+        }
+
+        impl ::conniecs::entity::Bundle<#cty> for #name {
+            fn remove(entity: ::conniecs::ModifyData<#cty>, components: &mut #cty) {
+                let _ = (entity, &components);
+                #(
+                    components.#list_names.remove(entity);
+                )*
+            }
+        }
+    }
+}
+
+/// The list a field goes in, which is the field's own name unless it has a
+/// `#[component(list_name)]` attribute.
+fn list_name(field: &Field) -> Ident {
+    for attr in &field.attrs {
+        let meta = attr.parse_meta().unwrap();
+        if meta.name() == "component" {
+            let list = read_path_item(&meta, || improper_component_format());
+            return Ident::new(&list, proc_macro2::Span::call_site());
+        }
+    }
+    field.ident.clone().unwrap()
+}
+
+fn improper_comp_format() -> ! {
+    improper_attr_format("#[components(...)]", "conniecs::entity::bundle")
+}
+
+fn improper_component_format() -> ! {
+    improper_attr_format("#[component(...)]", "conniecs::entity::bundle")
+}
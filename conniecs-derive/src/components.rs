@@ -1,5 +1,8 @@
-use quote;
-use syn::{Data, Field, Fields, Ident, Meta};
+use crate::{improper_attr_format, quote_path, read_path_item};
+
+use proc_macro2::Span;
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Data, Field, Fields, Ident, Meta};
 
 pub fn impl_components(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
     if ast.generics != Default::default() {
@@ -23,12 +26,19 @@ pub fn impl_components(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
         }
     };
 
-    let init = if let Some(ref fields) = fields {
+    let init = if let Some(fields) = fields {
         let field_inits = fields
             .iter()
-            .map(|field| field_info(field))
-            .map(|(ident, kind)| {
-                quote! { #ident: ::conniecs::component::ComponentList::#kind() }
+            .map(field_info)
+            .enumerate()
+            .map(|(i, (ident, list))| {
+                // Lists past the size of a signature fall back to looking components up
+                if i < 64 {
+                    let bit = i as u32;
+                    quote! { #ident: #list.__with_signature_bit(#bit) }
+                } else {
+                    quote! { #ident: #list }
+                }
             });
 
         quote! {
@@ -40,13 +50,24 @@ pub fn impl_components(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
         quote! { #name }
     };
 
-    let wipe = if let Some(ref fields) = fields {
-        let fields = fields.iter().map(|field| field.ident.clone());
-        quote! {
-            #(
-                self.#fields.__wipe();
-            )*
-        }
+    let field_names = match fields {
+        Some(fields) => fields.iter().map(|field| field.ident.clone()).collect(),
+        None => vec![],
+    };
+    let field_names = &field_names;
+    // quote can't use the same variable twice within one repetition
+    let ptr_names = field_names;
+    let field_strs = field_names
+        .iter()
+        .map(|ident| ident.as_ref().unwrap().to_string());
+    let list_names = field_names
+        .iter()
+        .map(|ident| ident.as_ref().unwrap().to_string());
+
+    // Only managers which ask for it are saved, so that enabling the feature
+    // doesn't require every component type to implement serde's traits
+    let serialize = if has_attr(&ast.attrs, "serialize") {
+        impl_serialize(&name, fields)
     } else {
         quote! {}
     };
@@ -57,27 +78,228 @@ pub fn impl_components(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
                 #init
             }
 
+            #[doc(hidden)]
+            fn __remove_all(&mut self, entity: &::conniecs::IndexedEntity<Self>) {
+                let _ = entity;
+                #(
+                    self.#field_names.__clear(entity);
+                )*
+            }
+
             #[doc(hidden)]
             fn __wipe_all(&mut self) {
-                #wipe
+                #(
+                    self.#field_names.__wipe();
+                )*
+            }
+
+            #[doc(hidden)]
+            fn __sync_signature(&self, entity: &::conniecs::IndexedEntity<Self>) {
+                let _ = entity;
+                #(
+                    self.#field_names.__sync_signature(entity);
+                )*
+            }
+
+            #[doc(hidden)]
+            fn __set_tick(&mut self, tick: ::conniecs::tracking::Tick) {
+                let _ = tick;
+                #(
+                    self.#field_names.__set_tick(tick);
+                )*
+            }
+
+            #[doc(hidden)]
+            unsafe fn __list_ptr(
+                this: *mut Self,
+                name: &str,
+            ) -> ::std::option::Option<*mut dyn ::std::any::Any> {
+                let _ = this;
+                match name {
+                    #(
+                        #field_strs => Some(::std::ptr::addr_of_mut!((*this).#ptr_names)),
+                    )*
+                    _ => None,
+                }
+            }
+
+            #[doc(hidden)]
+            fn __clone_onto(
+                &mut self,
+                from: &::conniecs::IndexedEntity<Self>,
+                to: &::conniecs::IndexedEntity<Self>,
+            ) {
+                #[allow(unused_imports)]
+                use ::conniecs::component::{__CloneComponent, __SkipComponent};
+                let _ = (from, to);
+                #(
+                    (&mut &mut ::conniecs::component::__Cloner(&mut self.#field_names))
+                        .__clone_onto(from, to);
+                )*
+            }
+
+            #[doc(hidden)]
+            fn __list_names() -> &'static [&'static str] {
+                &[#(#list_names),*]
             }
 
             #[doc(hidden)]
             fn __please_use_the_derive_attribute() {}
         }
+
+        #serialize
     }
 }
 
-fn field_info(field: &Field) -> (&Ident, Ident) {
+#[cfg(feature = "serde")]
+fn impl_serialize(
+    name: &Ident,
+    fields: Option<&Punctuated<Field, Token![,]>>,
+) -> proc_macro2::TokenStream {
+    let saved: Vec<&Field> = fields
+        .into_iter()
+        .flatten()
+        .filter(|field| !has_flag(field, "transient"))
+        .collect();
+    let idents: Vec<&Ident> = saved.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    let idents = &idents;
+    // quote can't use the same variable twice within one repetition
+    let list_idents = idents;
+    let names = idents.iter().map(|ident| ident.to_string());
+    let types = saved.iter().map(|f| &f.ty);
+    let name_str = name.to_string();
+    let count = saved.len();
+
+    let lists = quote! {
+        #[derive(::conniecs::__serde::Deserialize)]
+        #[serde(crate = "::conniecs::__serde")]
+        struct Lists {
+            #(
+                #[serde(default)]
+                #idents: ::std::vec::Vec<(
+                    ::conniecs::Entity,
+                    <#types as ::conniecs::serialize::__ListComponent>::Component,
+                )>,
+            )*
+        }
+    };
+
+    let serialize = quote! {
+        fn serialize_components<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+        where
+            S: ::conniecs::__serde::Serializer,
+        {
+            use ::conniecs::__serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct(#name_str, #count)?;
+            #(
+                state.serialize_field(#names, &self.#idents)?;
+            )*
+            state.end()
+        }
+    };
+
+    let deserialize = quote! {
+        fn deserialize_components<'de, D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+        where
+            D: ::conniecs::__serde::Deserializer<'de>,
+        {
+            #lists
+
+            let lists = <Lists as ::conniecs::__serde::Deserialize>::deserialize(deserializer)?;
+            let mut components = <Self as ::conniecs::ComponentManager>::build_manager();
+            #(
+                for (entity, component) in lists.#idents {
+                    components.#list_idents.__load(entity, component);
+                }
+            )*
+            Ok(components)
+        }
+    };
+
+    let check = quote! {
+        #[doc(hidden)]
+        fn __check_entities<F>(&self, valid: F) -> ::std::result::Result<(), ::conniecs::Entity>
+        where
+            F: Fn(::conniecs::Entity) -> bool,
+        {
+            let _ = &valid;
+            #(
+                for (entity, _) in self.#idents.iter() {
+                    if !valid(entity) {
+                        return Err(entity);
+                    }
+                }
+            )*
+            Ok(())
+        }
+    };
+
+    quote! {
+        impl ::conniecs::serialize::SerializeComponents for #name {
+            #serialize
+            #deserialize
+            #check
+        }
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn impl_serialize(_: &Ident, _: Option<&Punctuated<Field, Token![,]>>) -> proc_macro2::TokenStream {
+    quote! {}
+}
+
+fn field_info(field: &Field) -> (&Ident, proc_macro2::TokenStream) {
+    let list = storage_constructor(field);
+    let list = if has_flag(field, "tracked") {
+        quote! { #list.track_changes() }
+    } else {
+        list
+    };
+    (field.ident.as_ref().unwrap(), list)
+}
+
+fn storage_constructor(field: &Field) -> proc_macro2::TokenStream {
     let kind_attr = field
         .attrs
         .iter()
         .filter_map(|a| a.parse_meta().ok())
-        .filter(|m| m.name() == "hot" || m.name() == "cold")
-        .nth(0);
+        .find(|m| m.name() == "hot" || m.name() == "cold" || m.name() == "storage");
 
-    match kind_attr {
-        Some(Meta::Word(kind)) => (field.ident.as_ref().unwrap(), kind),
-        _ => panic!("All component lists must be marked with either #[hot] or #[cold]"),
-    }
+    let kind = match kind_attr {
+        Some(Meta::Word(kind)) => kind.to_string(),
+        Some(ref meta @ Meta::List(_)) => read_path_item(meta, || improper_storage_fmt()),
+        _ => panic!("All component lists must be marked with either #[hot] or #[storage(cold)]"),
+    };
+
+    let constructor = match kind.as_str() {
+        "hot" | "VecMap" => "hot",
+        "cold" | "HashMap" => "cold",
+        "dense" | "DenseVec" => "dense",
+        "flag" | "Flag" => "flag",
+        "" => improper_storage_fmt(),
+        path => {
+            let path = quote_path(path);
+            return quote! {
+                ::conniecs::component::ComponentList::with_storage(#path::default())
+            };
+        }
+    };
+
+    let constructor = Ident::new(constructor, Span::call_site());
+    quote! { ::conniecs::component::ComponentList::#constructor() }
+}
+
+fn has_flag(field: &Field, flag: &str) -> bool {
+    has_attr(&field.attrs, flag)
+}
+
+fn has_attr(attrs: &[Attribute], name: &str) -> bool {
+    attrs
+        .iter()
+        .filter_map(|a| a.parse_meta().ok())
+        .any(|m| m.name() == name)
+}
+
+fn improper_storage_fmt() -> ! {
+    improper_attr_format("#[storage(...)]", "conniecs::storage")
 }
@@ -11,6 +11,7 @@ use quote::TokenStreamExt;
 use syn::DeriveInput;
 
 mod aspect;
+mod bundle;
 mod components;
 mod services;
 mod system;
@@ -29,7 +30,24 @@ pub fn derive_aspect(input: TokenStream) -> TokenStream {
     result.into()
 }
 
-#[proc_macro_derive(ComponentManager, attributes(hot, cold))]
+#[proc_macro_derive(Bundle, attributes(components, component))]
+pub fn derive_bundle(input: TokenStream) -> TokenStream {
+    // Parse the string representation
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    // Build the impl
+    let result = bundle::impl_bundle(ast);
+
+    // Return the generated impl
+    result.into()
+}
+
+// `cold` isn't registered as a helper since it collides with the builtin
+// `#[cold]` attribute, so cold lists have to be marked `#[storage(cold)]`.
+#[proc_macro_derive(
+    ComponentManager,
+    attributes(hot, storage, tracked, transient, serialize)
+)]
 pub fn derive_components(input: TokenStream) -> TokenStream {
     // Parse the string representation
     let ast = parse_macro_input!(input as DeriveInput);
@@ -41,7 +59,10 @@ pub fn derive_components(input: TokenStream) -> TokenStream {
     result.into()
 }
 
-#[proc_macro_derive(ServiceManager)]
+#[proc_macro_derive(
+    ServiceManager,
+    attributes(events, on_begin_frame, on_end_frame, on_wipe)
+)]
 pub fn derive_services(input: TokenStream) -> TokenStream {
     // Parse the string representation
     let ast = parse_macro_input!(input as DeriveInput);
@@ -64,9 +85,14 @@ pub fn derive_services(input: TokenStream) -> TokenStream {
         aspect_b,
         interval,
         timed_interval,
+        timestep,
+        max_steps,
         activated,
         reactivated,
-        deactivated
+        deactivated,
+        reads,
+        writes,
+        read_events
     )
 )]
 pub fn derive_system(input: TokenStream) -> TokenStream {
@@ -80,7 +106,10 @@ pub fn derive_system(input: TokenStream) -> TokenStream {
     result.into()
 }
 
-#[proc_macro_derive(SystemManager, attributes(data, passive))]
+#[proc_macro_derive(
+    SystemManager,
+    attributes(data, parallel, passive, before, after, stage, stages)
+)]
 pub fn derive_systems(input: TokenStream) -> TokenStream {
     // Parse the string representation
     let ast = parse_macro_input!(input as DeriveInput);
@@ -143,7 +172,7 @@ fn quote_path(path: &str) -> proc_macro2::TokenStream {
                 Punct::new(':', Spacing::Joint),
             ]);
         }
-        if part.len() > 0 {
+        if !part.is_empty() {
             tokens.append(Ident::new(part, Span::call_site()));
         }
     }
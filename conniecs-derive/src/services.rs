@@ -1,13 +1,134 @@
-use syn;
-use quote;
+use crate::{improper_attr_format, quote_path, read_path_item};
+
+use syn::{Data, Field, Fields};
 
 pub fn impl_services(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
     let name = ast.ident;
 
+    let fields = match &ast.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().collect(),
+            _ => vec![],
+        },
+        _ => vec![],
+    };
+    let field_names = fields
+        .iter()
+        .map(|field| field.ident.as_ref().unwrap())
+        .collect::<Vec<_>>();
+    let field_names = &field_names;
+    let field_strs = field_names.iter().map(|ident| ident.to_string());
+    let field_tys = &fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+    let channels = fields
+        .iter()
+        .filter(|field| {
+            field
+                .attrs
+                .iter()
+                .filter_map(|a| a.parse_meta().ok())
+                .any(|meta| meta.name() == "events")
+        })
+        .map(|field| field.ident.as_ref().unwrap());
+    let begin_frame = quote_hooks(&fields, "on_begin_frame", "begin_frame");
+    let end_frame = quote_hooks(&fields, "on_end_frame", "end_frame");
+    let wipe = quote_hooks(&fields, "on_wipe", "wipe");
+
     quote!{
         impl ::conniecs::services::ServiceManager for #name {
+            #[doc(hidden)]
+            unsafe fn __service_ptr(
+                this: *mut Self,
+                name: &str,
+            ) -> ::std::option::Option<*mut dyn ::std::any::Any> {
+                let _ = this;
+                match name {
+                    #(
+                        #field_strs => Some(::std::ptr::addr_of_mut!((*this).#field_names)),
+                    )*
+                    _ => None,
+                }
+            }
+
+            #[doc(hidden)]
+            fn __service_by_type(
+                &self,
+                ty: ::std::any::TypeId,
+            ) -> ::std::option::Option<&dyn ::std::any::Any> {
+                let _ = ty;
+                #(
+                    if ty == ::std::any::TypeId::of::<#field_tys>() {
+                        return Some(&self.#field_names);
+                    }
+                )*
+                None
+            }
+
+            #[doc(hidden)]
+            fn __service_by_type_mut(
+                &mut self,
+                ty: ::std::any::TypeId,
+            ) -> ::std::option::Option<&mut dyn ::std::any::Any> {
+                let _ = ty;
+                #(
+                    if ty == ::std::any::TypeId::of::<#field_tys>() {
+                        return Some(&mut self.#field_names);
+                    }
+                )*
+                None
+            }
+
+            #[doc(hidden)]
+            fn __swap_events(&mut self) {
+                #(
+                    ::conniecs::event::EventChannel::swap(&mut self.#channels);
+                )*
+            }
+
+            #[doc(hidden)]
+            fn __begin_frame(&mut self) {
+                #begin_frame
+            }
+
+            #[doc(hidden)]
+            fn __end_frame(&mut self) {
+                #end_frame
+            }
+
+            #[doc(hidden)]
+            fn __wipe(&mut self) {
+                #wipe
+            }
+
             #[doc(hidden)]
             fn __please_use_the_derive_attribute() {}
         }
     }
 }
+
+/// Calls for each field with the hook attribute `attr`, in field order. A
+/// bare attribute calls the `ServiceHooks` method, and `#[attr(function)]`
+/// calls the function with the field instead.
+fn quote_hooks(fields: &[&Field], attr: &str, method: &str) -> proc_macro2::TokenStream {
+    let mut calls = proc_macro2::TokenStream::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        for meta in field.attrs.iter().filter_map(|a| a.parse_meta().ok()) {
+            if meta.name() != attr {
+                continue;
+            }
+
+            let function = match meta {
+                syn::Meta::Word(_) => {
+                    quote_path(&format!("::conniecs::services::ServiceHooks::{}", method))
+                }
+                _ => quote_path(&read_path_item(&meta, || improper_hook_format(attr))),
+            };
+            calls.extend(quote! { #function(&mut self.#ident); });
+        }
+    }
+    calls
+}
+
+fn improper_hook_format(attr: &str) -> ! {
+    improper_attr_format(&format!("#[{}(...)]", attr), "conniecs::services")
+}
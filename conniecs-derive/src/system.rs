@@ -1,4 +1,4 @@
-use crate::aspect::{quote_aspect, read_aspect_meta};
+use crate::aspect::{quote_aspect, read_aspect_meta, AspectExpr};
 use crate::{improper_attr_format, quote_path, read_path_item};
 
 use proc_macro2::Span;
@@ -10,6 +10,7 @@ enum SystemType {
     Lazy,
     Interval,
     Interact,
+    Fixed,
 }
 
 pub fn impl_system(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
@@ -17,9 +18,8 @@ pub fn impl_system(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
 
     for attr in &ast.attrs {
         let meta = attr.parse_meta().unwrap();
-        match meta.name().to_string().as_str() {
-            "system_type" => kind = read_systy(&meta),
-            _ => (),
+        if meta.name() == "system_type" {
+            kind = read_systy(&meta);
         }
     }
 
@@ -29,6 +29,7 @@ pub fn impl_system(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
         SystemType::Lazy => impl_lazy_system(&ast),
         SystemType::Interval => impl_interval_system(&ast),
         SystemType::Interact => impl_interact_system(&ast),
+        SystemType::Fixed => impl_fixed_system(&ast),
     }
 }
 
@@ -60,19 +61,13 @@ fn impl_basic_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     };
 
     let process = if let Some(proc_func) = process_func {
-        let proc_func = quote_path(&proc_func);
-        quote! {
-            impl ::conniecs::system::Process for #name {
-                fn process(&mut self, data: &mut ::conniecs::DataHelper<Self::Components, Self::Services>) {
-                    #proc_func(self, data);
-                }
-            }
-        }
+        quote_process(name, &quote_path(&proc_func), read_access(&ast.attrs))
     } else {
         quote! {}
     };
 
     let activations = read_activations(&ast.attrs);
+    let checks = quote_checks(&ast.attrs);
 
     quote! {
         impl ::conniecs::system::System for #name {
@@ -80,6 +75,7 @@ fn impl_basic_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
             type Services = #services;
 
             fn build_system() -> Self {
+                #checks
                 #init
             }
 
@@ -95,8 +91,7 @@ fn impl_entity_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     let mut cs_data = None;
     let mut init_func = None;
     let mut process_func = None;
-    let mut aspect_all = vec![];
-    let mut aspect_none = vec![];
+    let mut aspect_expr = AspectExpr::default();
 
     let aspect_id = Ident::new(&format!("{}EntityAspect", name), Span::call_site());
     let mut aspect_path = None;
@@ -107,7 +102,7 @@ fn impl_entity_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
             "data" => cs_data = Some(read_data(&meta)),
             "init" => init_func = Some(read_path_item(&meta, || improper_init_fmt())),
             "process" => process_func = Some(read_path_item(&meta, || improper_process_fmt())),
-            "aspect" => aspect_path = read_aspect_meta(&meta, &mut aspect_all, &mut aspect_none),
+            "aspect" => aspect_path = read_aspect_meta(&meta, &mut aspect_expr),
             _ => (),
         }
     }
@@ -124,14 +119,39 @@ fn impl_entity_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     };
 
     let datahelper = quote! { ::conniecs::world::DataHelper<Self::Components, Self::Services> };
-    let entiter = quote! { ::conniecs::entity::EntityIter<Self::Components> };
+    let cs = quote_system_data(name);
+    let entiter = quote! {
+        ::conniecs::entity::EntityIter<<#name as ::conniecs::system::System>::Components>
+    };
+    let splitdata = quote! { ::conniecs::system::SplitData<#cs> };
 
     let process = if let Some(proc_func) = process_func {
         let proc_func = quote_path(&proc_func);
-        quote! {
-            impl ::conniecs::system::entity::EntityProcess for #name {
-                fn process(&mut self, entities: #entiter, data: &mut #datahelper) {
-                    #proc_func(self, entities, data);
+        if let Some(access) = read_access(&ast.attrs) {
+            quote! {
+                impl ::conniecs::system::entity::EntityProcess for #name {
+                    fn process(&mut self, entities: #entiter, data: &mut #datahelper) {
+                        let access = #access;
+                        data.__split(access, |data| #proc_func(self, entities, data));
+                    }
+
+                    fn split(&mut self) -> Option<::conniecs::system::entity::SplitEntity<'_, #cs>> {
+                        Some((#access, self))
+                    }
+                }
+
+                impl ::conniecs::system::entity::SplitEntityProcess<#cs> for #name {
+                    fn process_split(&mut self, entities: #entiter, data: &mut #splitdata) {
+                        #proc_func(self, entities, data);
+                    }
+                }
+            }
+        } else {
+            quote! {
+                impl ::conniecs::system::entity::EntityProcess for #name {
+                    fn process(&mut self, entities: #entiter, data: &mut #datahelper) {
+                        #proc_func(self, entities, data);
+                    }
                 }
             }
         }
@@ -142,7 +162,7 @@ fn impl_entity_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     let (aspect, aspect_id) = if let Some(aspect_path) = aspect_path {
         (quote! {}, aspect_path)
     } else {
-        let aspect = quote_aspect(&aspect_id, &components, &aspect_all, &aspect_none);
+        let aspect = quote_aspect(&aspect_id, &components, &aspect_expr);
         let aspect = quote! { #[derive(Copy, Clone, Debug)] pub struct #aspect_id; #aspect };
         (aspect, quote! { #aspect_id })
     };
@@ -156,6 +176,7 @@ fn impl_entity_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     };
 
     let activations = read_activations(&ast.attrs);
+    let checks = quote_checks(&ast.attrs);
 
     quote! {
         impl ::conniecs::system::System for #name {
@@ -163,6 +184,7 @@ fn impl_entity_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
             type Services = #services;
 
             fn build_system() -> Self {
+                #checks
                 #init
             }
 
@@ -195,19 +217,13 @@ fn impl_lazy_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     };
 
     let process = if let Some(proc_func) = process_func {
-        let proc_func = quote_path(&proc_func);
-        quote! {
-            impl ::conniecs::system::Process for #name {
-                fn process(&mut self, data: &mut ::conniecs::DataHelper<Self::Components, Self::Services>) {
-                    #proc_func(self, data);
-                }
-            }
-        }
+        quote_process(name, &quote_path(&proc_func), read_access(&ast.attrs))
     } else {
         quote! {}
     };
 
     let activations = read_activations(&ast.attrs);
+    let checks = quote_checks(&ast.attrs);
 
     quote! {
         impl ::conniecs::system::System for #name {
@@ -215,6 +231,7 @@ fn impl_lazy_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
             type Services = #services;
 
             fn build_system() -> Self {
+                #checks
                 unimplemented!()
             }
 
@@ -255,14 +272,7 @@ fn impl_interval_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     };
 
     let process = if let Some(proc_func) = process_func {
-        let proc_func = quote_path(&proc_func);
-        quote! {
-            impl ::conniecs::system::Process for #name {
-                fn process(&mut self, data: &mut ::conniecs::DataHelper<Self::Components, Self::Services>) {
-                    #proc_func(self, data);
-                }
-            }
-        }
+        quote_process(name, &quote_path(&proc_func), read_access(&ast.attrs))
     } else {
         quote! {}
     };
@@ -278,6 +288,7 @@ fn impl_interval_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     };
 
     let activations = read_activations(&ast.attrs);
+    let checks = quote_checks(&ast.attrs);
 
     quote! {
         impl ::conniecs::system::System for #name {
@@ -285,6 +296,7 @@ fn impl_interval_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
             type Services = #services;
 
             fn build_system() -> Self {
+                #checks
                 #init
             }
 
@@ -313,6 +325,15 @@ fn parse_u64(time: &str) -> u64 {
 }
 
 fn parse_iv_time(time: &str) -> proc_macro2::TokenStream {
+    match parse_time_ns(time) {
+        Some(ns) => frame_ns(ns),
+        None => frame_iv(parse_u64(time)),
+    }
+}
+
+/// Parses a time with a unit suffix into nanoseconds, or returns `None` if it
+/// has no unit.
+fn parse_time_ns(time: &str) -> Option<u64> {
     let len = time.len();
 
     let ns = if time.ends_with("ms") && len > 2 {
@@ -322,17 +343,15 @@ fn parse_iv_time(time: &str) -> proc_macro2::TokenStream {
         let iv = parse_u64(&time[..len - 2]);
         iv * 1_000
     } else if time.ends_with("ns") && len > 2 {
-        let iv = parse_u64(&time[..len - 2]);
-        iv
+        parse_u64(&time[..len - 2])
     } else if time.ends_with("s") && len > 1 {
         let iv = parse_u64(&time[..len - 1]);
         iv * 1_000_000_000
     } else {
-        let iv = parse_u64(time);
-        return frame_iv(iv);
+        return None;
     };
 
-    frame_ns(ns)
+    Some(ns)
 }
 
 fn parse_explicit_iv<'a>(
@@ -387,15 +406,114 @@ fn frame_ns(ns: u64) -> proc_macro2::TokenStream {
     }
 }
 
+fn impl_fixed_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
+    let name = &ast.ident;
+    let mut cs_data = None;
+    let mut init_func = None;
+    let mut process_func = None;
+    let mut timestep = None;
+    let mut max_steps = 5u32;
+
+    for attr in &ast.attrs {
+        let meta = attr.parse_meta().unwrap();
+        match meta.name().to_string().as_str() {
+            "data" => cs_data = Some(read_data(&meta)),
+            "init" => init_func = Some(read_path_item(&meta, || improper_init_fmt())),
+            "process" => process_func = Some(read_path_item(&meta, || improper_process_fmt())),
+            "timestep" => {
+                let time = read_path_item(&meta, || improper_timestep_fmt());
+                timestep = Some(parse_time_ns(&time).unwrap_or_else(|| improper_timestep_fmt()));
+            }
+            "max_steps" => max_steps = parse_max_steps(&meta),
+            "reads" | "writes" => {
+                panic!(
+                    "Fixed timestep systems can't declare their access with #[reads] or #[writes]"
+                )
+            }
+            _ => (),
+        }
+    }
+
+    let (components, services) = match cs_data {
+        Some((c, s)) => (c, s),
+        None => (quote_path("crate::Components"), quote_path("crate::Services")),
+    };
+
+    let init = if let Some(init_func) = init_func {
+        quote! { #init_func() }
+    } else {
+        quote! { Default::default() }
+    };
+
+    let datahelper = quote! { ::conniecs::world::DataHelper<Self::Components, Self::Services> };
+
+    let process = if let Some(proc_func) = process_func {
+        let proc_func = quote_path(&proc_func);
+        quote! {
+            impl ::conniecs::system::fixed::FixedProcess for #name {
+                fn process(&mut self, dt: f64, data: &mut #datahelper) {
+                    #proc_func(self, dt, data);
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let step = timestep.unwrap_or_else(|| panic!("#[timestep = ...] attribute must be specified"));
+    if step == 0 {
+        improper_timestep_fmt();
+    }
+
+    let create_timestep = quote! {
+        impl ::conniecs::system::fixed::SystemTimestep for #name {
+            fn create_timestep() -> ::conniecs::system::fixed::Timestep {
+                ::conniecs::system::fixed::Timestep::new(#step, #max_steps)
+            }
+        }
+    };
+
+    let activations = read_activations(&ast.attrs);
+    let checks = quote_checks(&ast.attrs);
+
+    quote! {
+        impl ::conniecs::system::System for #name {
+            type Components = #components;
+            type Services = #services;
+
+            fn build_system() -> Self {
+                #checks
+                #init
+            }
+
+            #activations
+        }
+
+        #process
+        #create_timestep
+    }
+}
+
+fn parse_max_steps(attr: &Meta) -> u32 {
+    if let Meta::NameValue(MetaNameValue {
+        lit: Lit::Int(steps),
+        ..
+    }) = attr
+    {
+        return steps.value() as u32;
+    }
+
+    let steps = read_path_item(attr, || improper_max_steps_fmt());
+    steps.parse().unwrap_or_else(|_| improper_max_steps_fmt())
+}
+
 fn impl_interact_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     let name = &ast.ident;
     let mut cs_data = None;
     let mut init_func = None;
     let mut process_func = None;
-    let mut aspect_all_a = vec![];
-    let mut aspect_none_a = vec![];
-    let mut aspect_all_b = vec![];
-    let mut aspect_none_b = vec![];
+    let mut aspect_expr_a = AspectExpr::default();
+    let mut aspect_expr_b = AspectExpr::default();
 
     let aspect_id_a = Ident::new(&format!("{}EntityAspectA", name), Span::call_site());
     let mut aspect_path_a = None;
@@ -408,12 +526,8 @@ fn impl_interact_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
             "data" => cs_data = Some(read_data(&meta)),
             "init" => init_func = Some(read_path_item(&meta, || improper_init_fmt())),
             "process" => process_func = Some(read_path_item(&meta, || improper_process_fmt())),
-            "aspect_a" => {
-                aspect_path_a = read_aspect_meta(&meta, &mut aspect_all_a, &mut aspect_none_a)
-            }
-            "aspect_b" => {
-                aspect_path_b = read_aspect_meta(&meta, &mut aspect_all_b, &mut aspect_none_b)
-            }
+            "aspect_a" => aspect_path_a = read_aspect_meta(&meta, &mut aspect_expr_a),
+            "aspect_b" => aspect_path_b = read_aspect_meta(&meta, &mut aspect_expr_b),
             _ => (),
         }
     }
@@ -430,14 +544,39 @@ fn impl_interact_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     };
 
     let datahelper = quote! { ::conniecs::world::DataHelper<Self::Components, Self::Services> };
-    let entiter = quote! { ::conniecs::entity::EntityIter<Self::Components> };
+    let cs = quote_system_data(name);
+    let entiter = quote! {
+        ::conniecs::entity::EntityIter<<#name as ::conniecs::system::System>::Components>
+    };
+    let splitdata = quote! { ::conniecs::system::SplitData<#cs> };
 
     let process = if let Some(proc_func) = process_func {
         let proc_func = quote_path(&proc_func);
-        quote! {
-            impl ::conniecs::system::interact::InteractProcess for #name {
-                fn process(&mut self, ea: #entiter, eb: #entiter, data: &mut #datahelper) {
-                    #proc_func(self, ea, eb, data);
+        if let Some(access) = read_access(&ast.attrs) {
+            quote! {
+                impl ::conniecs::system::interact::InteractProcess for #name {
+                    fn process(&mut self, ea: #entiter, eb: #entiter, data: &mut #datahelper) {
+                        let access = #access;
+                        data.__split(access, |data| #proc_func(self, ea, eb, data));
+                    }
+
+                    fn split(&mut self) -> Option<::conniecs::system::interact::SplitInteract<'_, #cs>> {
+                        Some((#access, self))
+                    }
+                }
+
+                impl ::conniecs::system::interact::SplitInteractProcess<#cs> for #name {
+                    fn process_split(&mut self, ea: #entiter, eb: #entiter, data: &mut #splitdata) {
+                        #proc_func(self, ea, eb, data);
+                    }
+                }
+            }
+        } else {
+            quote! {
+                impl ::conniecs::system::interact::InteractProcess for #name {
+                    fn process(&mut self, ea: #entiter, eb: #entiter, data: &mut #datahelper) {
+                        #proc_func(self, ea, eb, data);
+                    }
                 }
             }
         }
@@ -448,7 +587,7 @@ fn impl_interact_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     let (aspect_a, aspect_id_a) = if let Some(aspect_path_a) = aspect_path_a {
         (quote! {}, aspect_path_a)
     } else {
-        let aspect_a = quote_aspect(&aspect_id_a, &components, &aspect_all_a, &aspect_none_a);
+        let aspect_a = quote_aspect(&aspect_id_a, &components, &aspect_expr_a);
         let aspect_a = quote! { #[derive(Copy, Clone, Debug)] pub struct #aspect_id_a; #aspect_a };
         (aspect_a, quote! { #aspect_id_a })
     };
@@ -456,7 +595,7 @@ fn impl_interact_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     let (aspect_b, aspect_id_b) = if let Some(aspect_path_b) = aspect_path_b {
         (quote! {}, aspect_path_b)
     } else {
-        let aspect_b = quote_aspect(&aspect_id_b, &components, &aspect_all_b, &aspect_none_b);
+        let aspect_b = quote_aspect(&aspect_id_b, &components, &aspect_expr_b);
         let aspect_b = quote! { #[derive(Copy, Clone, Debug)] pub struct #aspect_id_b; #aspect_b };
         (aspect_b, quote! { #aspect_id_b })
     };
@@ -473,6 +612,7 @@ fn impl_interact_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     };
 
     let activations = read_activations(&ast.attrs);
+    let checks = quote_checks(&ast.attrs);
 
     quote! {
         impl ::conniecs::system::System for #name {
@@ -480,6 +620,7 @@ fn impl_interact_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
             type Services = #services;
 
             fn build_system() -> Self {
+                #checks
                 #init
             }
 
@@ -493,6 +634,215 @@ fn impl_interact_system(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
     }
 }
 
+fn quote_process(
+    name: &Ident,
+    proc_func: &proc_macro2::TokenStream,
+    access: Option<proc_macro2::TokenStream>,
+) -> proc_macro2::TokenStream {
+    let datahelper = quote! { ::conniecs::DataHelper<Self::Components, Self::Services> };
+
+    if let Some(access) = access {
+        let cs = quote_system_data(name);
+        let splitdata = quote! { ::conniecs::system::SplitData<#cs> };
+        quote! {
+            impl ::conniecs::system::Process for #name {
+                fn process(&mut self, data: &mut #datahelper) {
+                    let access = #access;
+                    data.__split(access, |data| #proc_func(self, data));
+                }
+
+                fn split(&mut self) -> Option<::conniecs::system::Split<'_, #cs>> {
+                    Some((#access, self))
+                }
+            }
+
+            impl ::conniecs::system::SplitProcess<#cs> for #name {
+                fn process_split(&mut self, data: &mut #splitdata) {
+                    #proc_func(self, data);
+                }
+            }
+        }
+    } else {
+        quote! {
+            impl ::conniecs::system::Process for #name {
+                fn process(&mut self, data: &mut #datahelper) {
+                    #proc_func(self, data);
+                }
+            }
+        }
+    }
+}
+
+/// The component and service managers of a system, for impls outside of
+/// `System` where `Self::Components` isn't available.
+fn quote_system_data(name: &Ident) -> proc_macro2::TokenStream {
+    quote! {
+        <#name as ::conniecs::system::System>::Components,
+        <#name as ::conniecs::system::System>::Services
+    }
+}
+
+/// The names given to `#[reads(...)]`, `#[writes(...)]` and
+/// `#[read_events(...)]`.
+#[derive(Default)]
+struct Declared {
+    reads: Vec<Ident>,
+    writes: Vec<Ident>,
+    reads_services: Vec<Ident>,
+    writes_services: Vec<Ident>,
+    reads_resources: bool,
+    writes_resources: bool,
+}
+
+/// Reads the access a system declared, if it declared any with `#[reads]` or
+/// `#[writes]`.
+fn read_declared(attrs: &[Attribute]) -> Option<Declared> {
+    let mut declared = false;
+    let mut access = Declared::default();
+
+    for attr in attrs {
+        let meta = attr.parse_meta().unwrap();
+        let (lists, services, resources) = match meta.name().to_string().as_str() {
+            "reads" => (
+                &mut access.reads,
+                &mut access.reads_services,
+                &mut access.reads_resources,
+            ),
+            "writes" => (
+                &mut access.writes,
+                &mut access.writes_services,
+                &mut access.writes_resources,
+            ),
+            "read_events" => {
+                access.reads_services.extend(read_event_names(&meta));
+                continue;
+            }
+            _ => continue,
+        };
+        declared = true;
+
+        let items = match &meta {
+            syn::Meta::List(list) => &list.nested,
+            _ => improper_access_fmt(),
+        };
+        for item in items {
+            match item {
+                syn::NestedMeta::Meta(syn::Meta::Word(word)) if word == "resources" => {
+                    *resources = true
+                }
+                syn::NestedMeta::Meta(syn::Meta::Word(word)) => lists.push(word.clone()),
+                syn::NestedMeta::Meta(syn::Meta::List(list)) if list.ident == "services" => {
+                    for item in &list.nested {
+                        match item {
+                            syn::NestedMeta::Meta(syn::Meta::Word(word)) => {
+                                services.push(word.clone())
+                            }
+                            _ => improper_access_fmt(),
+                        }
+                    }
+                }
+                _ => improper_access_fmt(),
+            }
+        }
+    }
+
+    if declared {
+        Some(access)
+    } else {
+        None
+    }
+}
+
+/// Reads `#[reads(...)]` and `#[writes(...)]` into an `Access`, if the system
+/// declared either.
+fn read_access(attrs: &[Attribute]) -> Option<proc_macro2::TokenStream> {
+    let access = read_declared(attrs)?;
+    let names = |idents: &[Ident]| idents.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+    let reads = names(&access.reads);
+    let writes = names(&access.writes);
+    let reads_services = names(&access.reads_services);
+    let writes_services = names(&access.writes_services);
+    let reads_resources = access.reads_resources;
+    let writes_resources = access.writes_resources;
+
+    Some(quote! {
+        ::conniecs::system::Access {
+            reads: &[#(#reads),*],
+            writes: &[#(#writes),*],
+            reads_services: &[#(#reads_services),*],
+            writes_services: &[#(#writes_services),*],
+            reads_resources: #reads_resources,
+            writes_resources: #writes_resources,
+        }
+    })
+}
+
+/// Checks which run when a system is built, so that they only have to
+/// compile.
+fn quote_checks(attrs: &[Attribute]) -> proc_macro2::TokenStream {
+    let events = quote_event_check(attrs);
+    let access = quote_access_check(attrs);
+    quote! {
+        #events
+        #access
+    }
+}
+
+/// Checks that every component list and service named in `#[reads(...)]` or
+/// `#[writes(...)]` is a field of the component or service manager.
+fn quote_access_check(attrs: &[Attribute]) -> proc_macro2::TokenStream {
+    let access = match read_declared(attrs) {
+        Some(access) => access,
+        None => return quote! {},
+    };
+    let lists = access.reads.iter().chain(&access.writes);
+    let services = access.reads_services.iter().chain(&access.writes_services);
+
+    quote! {
+        let _ = |components: &Self::Components, services: &Self::Services| {
+            #( let _ = &components.#lists; )*
+            #( let _ = &services.#services; )*
+        };
+    }
+}
+
+/// Checks that every channel named in `#[read_events(...)]` is an
+/// `EventChannel` service read through a `ReaderId` field of the same name.
+fn quote_event_check(attrs: &[Attribute]) -> proc_macro2::TokenStream {
+    let channels = attrs
+        .iter()
+        .map(|attr| attr.parse_meta().unwrap())
+        .filter(|meta| meta.name() == "read_events")
+        .flat_map(|meta| read_event_names(&meta))
+        .collect::<Vec<_>>();
+    if channels.is_empty() {
+        return quote! {};
+    }
+    let readers = channels.clone();
+
+    quote! {
+        let _ = |system: &mut Self, services: &Self::Services| {
+            #(
+                ::conniecs::event::__check_reader(&mut system.#readers, &services.#channels);
+            )*
+        };
+    }
+}
+
+fn read_event_names(meta: &Meta) -> Vec<Ident> {
+    let items = match meta {
+        Meta::List(list) => &list.nested,
+        _ => improper_read_events_fmt(),
+    };
+    items
+        .iter()
+        .map(|item| match item {
+            NestedMeta::Meta(Meta::Word(word)) => word.clone(),
+            _ => improper_read_events_fmt(),
+        })
+        .collect()
+}
+
 fn read_activations(attrs: &[Attribute]) -> proc_macro2::TokenStream {
     let mut activated = None;
     let mut reactivated = None;
@@ -557,6 +907,7 @@ fn read_systy(attr: &Meta) -> SystemType {
         "Lazy" | "lazy" => SystemType::Lazy,
         "Interval" | "interval" => SystemType::Interval,
         "Interact" | "interact" => SystemType::Interact,
+        "Fixed" | "fixed" => SystemType::Fixed,
         _ => improper_systy_fmt(),
     }
 }
@@ -621,6 +972,25 @@ fn improper_interval_fmt() -> ! {
     improper_attr_format("#[interval = ...]", "conniecs::system");
 }
 
+fn improper_timestep_fmt() -> ! {
+    improper_attr_format("#[timestep = ...]", "conniecs::system::fixed")
+}
+
+fn improper_max_steps_fmt() -> ! {
+    improper_attr_format("#[max_steps = ...]", "conniecs::system::fixed")
+}
+
+fn improper_access_fmt() -> ! {
+    improper_attr_format(
+        "#[reads(...)] or #[writes(...)]",
+        "conniecs::system::parallel",
+    )
+}
+
+fn improper_read_events_fmt() -> ! {
+    improper_attr_format("#[read_events(...)]", "conniecs::event")
+}
+
 fn improper_activated_fmt() -> ! {
     improper_attr_format("#[activated = ...]", "conniecs::system");
 }
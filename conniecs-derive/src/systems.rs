@@ -1,18 +1,22 @@
 use syn::{self, Attribute, Data, Fields};
-use quote;
 
 use crate::system::read_data;
-use crate::quote_path;
+use crate::{improper_attr_format, quote_path, read_path_item};
 
 pub fn impl_systems(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
     let name = &ast.ident;
     let mut cs_data = None;
+    let mut stages = Vec::new();
+    let mut parallel = false;
 
     for attr in &ast.attrs {
         let meta = attr.parse_meta().unwrap();
-        match meta.name().to_string().as_str() {
-            "data" => cs_data = Some(read_data(&meta)),
-            _ => (),
+        if meta.name() == "data" {
+            cs_data = Some(read_data(&meta));
+        } else if meta.name() == "parallel" {
+            parallel = true;
+        } else if meta.name() == "stages" {
+            stages = read_names(&meta, || improper_stages_fmt());
         }
     }
 
@@ -32,7 +36,7 @@ pub fn impl_systems(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
         }
     };
 
-    let init = if let Some(ref fields) = fields {
+    let init = if let Some(fields) = fields {
         let field_inits = fields
             .iter()
             .map(|field| field.ident.as_ref().unwrap())
@@ -52,10 +56,14 @@ pub fn impl_systems(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
     let empty = syn::punctuated::Punctuated::new();
     let fields = fields.unwrap_or(&empty);
 
-    let active_systems = fields
+    let (order, after) = schedule(fields, &stages);
+    let active = order
+        .into_iter()
+        .filter(|&i| !is_passive(&fields[i].attrs))
+        .collect::<Vec<_>>();
+    let active_systems = active
         .iter()
-        .filter(|field| !is_passive(&field.attrs))
-        .map(|field| field.ident.as_ref().unwrap())
+        .map(|&i| fields[i].ident.as_ref().unwrap())
         .collect::<Vec<_>>();
 
     let fields = fields
@@ -63,6 +71,7 @@ pub fn impl_systems(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
         .map(|field| field.ident.as_ref().unwrap())
         .collect::<Vec<_>>();
     let fields = &fields;
+    let field_names = fields.iter().map(|field| field.to_string());
 
     let (components, services) = match cs_data {
         Some((c, s)) => (c, s),
@@ -111,12 +120,26 @@ pub fn impl_systems(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
         }
     };
 
-    let update = quote! {
-        fn update(&mut self, data: &mut ::conniecs::DataHelper<Self::Components, Self::Services>) {
-            use conniecs::system::Process;
-            #(
-                Process::process(&mut self.#active_systems, data);
-            )*
+    let update = if parallel {
+        let runs = active.iter().zip(&active_systems).map(|(&i, system)| {
+            let after = &after[i];
+            quote! { batches.run(#i, &mut self.#system, &[#(#after),*]); }
+        });
+        quote! {
+            fn update(&mut self, data: &mut ::conniecs::DataHelper<Self::Components, Self::Services>) {
+                let mut batches = ::conniecs::system::parallel::__Batches::new(data);
+                #(#runs)*
+                batches.finish();
+            }
+        }
+    } else {
+        quote! {
+            fn update(&mut self, data: &mut ::conniecs::DataHelper<Self::Components, Self::Services>) {
+                use conniecs::system::Process;
+                #(
+                    Process::process(&mut self.#active_systems, data);
+                )*
+            }
         }
     };
 
@@ -134,6 +157,19 @@ pub fn impl_systems(ast: syn::DeriveInput) -> proc_macro2::TokenStream {
             #deactivated
             #update
 
+            fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+                use conniecs::system::System;
+                match name {
+                    #(
+                        #field_names => self.#fields.__set_enabled(enabled),
+                    )*
+                    _ => {
+                        let _ = enabled;
+                        false
+                    }
+                }
+            }
+
             #[doc(hidden)]
             fn __please_use_the_derive_attribute() {}
         }
@@ -150,3 +186,154 @@ fn is_passive(attrs: &[Attribute]) -> bool {
 
     false
 }
+
+/// Orders the systems so that every `#[before]`, `#[after]` and `#[stage]`
+/// constraint is met, otherwise keeping them in field order. Also returns
+/// every system each one has to run after, whether it was named directly or
+/// ordered through other systems.
+fn schedule(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    stages: &[String],
+) -> (Vec<usize>, Vec<Vec<usize>>) {
+    let names = fields
+        .iter()
+        .map(|field| field.ident.as_ref().unwrap().to_string())
+        .collect::<Vec<_>>();
+    let find = |name: &str| {
+        names
+            .iter()
+            .position(|n| n == name)
+            .unwrap_or_else(|| panic!("There is no system named `{}` to order against", name))
+    };
+
+    let mut edges = vec![Vec::new(); names.len()];
+    let mut system_stages = vec![None; names.len()];
+    for (i, field) in fields.iter().enumerate() {
+        for attr in &field.attrs {
+            let meta = attr.parse_meta().unwrap();
+            match &meta.name().to_string()[..] {
+                "before" => {
+                    for other in read_names(&meta, || improper_before_fmt()) {
+                        edges[i].push(find(&other));
+                    }
+                }
+                "after" => {
+                    for other in read_names(&meta, || improper_after_fmt()) {
+                        edges[find(&other)].push(i);
+                    }
+                }
+                "stage" => {
+                    let stage = read_path_item(&meta, || improper_stage_fmt());
+                    let stage = stages.iter().position(|s| *s == stage).unwrap_or_else(|| {
+                        panic!("Stage `{}` is not listed in #[stages(...)]", stage)
+                    });
+                    system_stages[i] = Some(stage);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    for (i, a) in system_stages.iter().enumerate() {
+        for (j, b) in system_stages.iter().enumerate() {
+            if let (Some(a), Some(b)) = (a, b) {
+                if a < b {
+                    edges[i].push(j);
+                }
+            }
+        }
+    }
+
+    let mut incoming = vec![0; names.len()];
+    let mut after = vec![Vec::new(); names.len()];
+    for (i, targets) in edges.iter().enumerate() {
+        for &j in targets {
+            incoming[j] += 1;
+            after[j].push(i);
+        }
+    }
+
+    let mut order = Vec::with_capacity(names.len());
+    let mut done = vec![false; names.len()];
+    while order.len() < names.len() {
+        let next = (0..names.len()).find(|&i| !done[i] && incoming[i] == 0);
+        let next = match next {
+            Some(next) => next,
+            None => {
+                let cycle = (0..names.len())
+                    .filter(|&i| !done[i])
+                    .map(|i| &names[i][..])
+                    .collect::<Vec<_>>();
+                panic!(
+                    "The systems {} can't be ordered because their ordering \
+                     constraints form a cycle",
+                    cycle.join(", ")
+                );
+            }
+        };
+
+        done[next] = true;
+        order.push(next);
+        for &j in &edges[next] {
+            incoming[j] -= 1;
+        }
+    }
+
+    // Passive systems never run in a batch, so a system has to know about
+    // everything ordered before the ones it names, not just those.
+    let mut preceding = vec![Vec::new(); names.len()];
+    for &j in &order {
+        let mut all = Vec::new();
+        for &i in &after[j] {
+            for &k in preceding[i].iter().chain(Some(&i)) {
+                if !all.contains(&k) {
+                    all.push(k);
+                }
+            }
+        }
+        all.sort();
+        preceding[j] = all;
+    }
+
+    (order, preceding)
+}
+
+fn read_names<F>(meta: &syn::Meta, fail: F) -> Vec<String>
+where
+    F: Fn(),
+{
+    let list = match meta {
+        syn::Meta::List(list) => list,
+        _ => {
+            fail();
+            unreachable!();
+        }
+    };
+
+    list.nested
+        .iter()
+        .map(|item| match item {
+            syn::NestedMeta::Meta(syn::Meta::Word(word)) => word.to_string(),
+            _ => {
+                fail();
+                unreachable!();
+            }
+        })
+        .collect()
+}
+
+fn improper_stages_fmt() -> ! {
+    improper_attr_format("#[stages(...)]", "conniecs::system")
+}
+
+fn improper_stage_fmt() -> ! {
+    improper_attr_format("#[stage(...)]", "conniecs::system")
+}
+
+fn improper_before_fmt() -> ! {
+    improper_attr_format("#[before(...)]", "conniecs::system")
+}
+
+fn improper_after_fmt() -> ! {
+    improper_attr_format("#[after(...)]", "conniecs::system")
+}
@@ -1,9 +1,71 @@
-//! TODO: Add documentation including describing how the derive macros work
+//! Filters deciding which entities a system is interested in.
+//!
+//! `#[derive(Aspect)]` on a unit struct builds a filter from an
+//! `#[aspect(...)]` attribute. The component manager defaults to
+//! `crate::Components`, and a different one can be given with
+//! `#[components(path::to::Components)]`. Every item of the attribute is a
+//! group, and an entity has to match all of them:
+//!
+//! - `all(a, b)` matches entities which have both `a` and `b`
+//! - `any(a, b)` matches entities which have at least one of them
+//! - `none(a, b)` matches entities which have neither
+//! - `not(a, b)` matches entities which don't have both, so it's "not all of"
+//!   rather than "none of": an entity with only `a` still matches
+//!
+//! Inside a group, items can be component names or further groups, so
+//! `all(a, any(b, c))` needs `a` and at least one of `b` and `c`. An empty
+//! `#[aspect()]` matches every entity.
+//!
+//! Systems can give the same attribute inline, or point at a derived aspect
+//! with `#[aspect = "path::to::Aspect"]`. Interact systems use `#[aspect_a]`
+//! and `#[aspect_b]` for their two groups.
+//!
+//! ```
+//! # #[macro_use] extern crate conniecs_derive; extern crate conniecs;
+//! use conniecs::Aspect;
+//! # #[derive(ComponentManager)] struct Components {
+//! #     #[hot] position: conniecs::ComponentList<Components, f32>,
+//! #     #[hot] velocity: conniecs::ComponentList<Components, f32>,
+//! #     #[hot] frozen: conniecs::ComponentList<Components, ()>,
+//! # }
+//! # #[derive(ServiceManager, Default)] struct Services {}
+//! # #[derive(SystemManager)] struct Systems {}
+//!
+//! /// Bodies which can move
+//! #[derive(Aspect)]
+//! #[components(Components)]
+//! #[aspect(all(position, velocity), none(frozen))]
+//! struct Moving;
+//!
+//! # fn main() {
+//! let mut world = conniecs::World::<Systems>::new();
+//! world.data.create_entity(|e, c, _| {
+//!     c.position.add(e, 0.0);
+//!     c.velocity.add(e, 1.0);
+//! });
+//! world.data.create_entity(|e, c, _| {
+//!     c.position.add(e, 0.0);
+//!     c.velocity.add(e, 1.0);
+//!     c.frozen.add(e, ());
+//! });
+//! world.update();
+//!
+//! let components = &world.data.components;
+//! let moving = Aspect::new(Moving);
+//! assert_eq!(world.entities().filter(moving, components).count(), 1);
+//! # }
+//! ```
+//!
+//! Aspects can also be put together at runtime with `Aspect::and`,
+//! `Aspect::or` and `!`, and any `Fn(EntityData<C>, &C) -> bool` which is
+//! `Send` and `Sync` works as a filter too.
+
+use std::ops;
 
 use crate::component::ComponentManager;
 use crate::entity::EntityData;
 
-pub struct Aspect<C: ComponentManager>(Box<AspectFilter<C> + 'static>);
+pub struct Aspect<C: ComponentManager>(Box<dyn AspectFilter<C> + 'static>);
 
 impl<C: ComponentManager> Aspect<C> {
     pub fn all() -> Self {
@@ -24,16 +86,44 @@ impl<C: ComponentManager> Aspect<C> {
     pub fn check<'a>(&self, entity: EntityData<'a, C>, components: &C) -> bool {
         self.0.check(entity, components)
     }
+
+    /// Matches entities which match both `self` and `other`.
+    pub fn and(self, other: Aspect<C>) -> Self {
+        Aspect::new(And(self, other))
+    }
+
+    /// Matches entities which match either `self` or `other`.
+    pub fn or(self, other: Aspect<C>) -> Self {
+        Aspect::new(Or(self, other))
+    }
+}
+
+impl<C: ComponentManager> ops::Not for Aspect<C> {
+    type Output = Aspect<C>;
+
+    /// Matches entities which don't match `self`.
+    fn not(self) -> Aspect<C> {
+        Aspect::new(Not(self))
+    }
+}
+
+impl<C: ComponentManager> AspectFilter<C> for Aspect<C> {
+    #[inline]
+    fn check<'a>(&self, entity: EntityData<'a, C>, components: &C) -> bool {
+        self.0.check(entity, components)
+    }
 }
 
-pub trait AspectFilter<C: ComponentManager>: 'static {
+/// Filters have to be `Send` and `Sync` so that systems watching them can run
+/// on other threads.
+pub trait AspectFilter<C: ComponentManager>: Send + Sync + 'static {
     fn check<'a>(&self, entity: EntityData<'a, C>, components: &C) -> bool;
 }
 
 impl<F, C> AspectFilter<C> for F
 where
     C: ComponentManager,
-    F: Fn(EntityData<C>, &C) -> bool + 'static,
+    F: Fn(EntityData<C>, &C) -> bool + Send + Sync + 'static,
 {
     #[inline]
     fn check<'a>(&self, entity: EntityData<'a, C>, components: &C) -> bool {
@@ -43,6 +133,9 @@ where
 
 struct All;
 struct None;
+struct And<C: ComponentManager>(Aspect<C>, Aspect<C>);
+struct Or<C: ComponentManager>(Aspect<C>, Aspect<C>);
+struct Not<C: ComponentManager>(Aspect<C>);
 
 impl<C> AspectFilter<C> for All
 where
@@ -63,3 +156,33 @@ where
         false
     }
 }
+
+impl<C> AspectFilter<C> for And<C>
+where
+    C: ComponentManager,
+{
+    #[inline]
+    fn check<'a>(&self, entity: EntityData<'a, C>, components: &C) -> bool {
+        self.0.check(entity, components) && self.1.check(entity, components)
+    }
+}
+
+impl<C> AspectFilter<C> for Or<C>
+where
+    C: ComponentManager,
+{
+    #[inline]
+    fn check<'a>(&self, entity: EntityData<'a, C>, components: &C) -> bool {
+        self.0.check(entity, components) || self.1.check(entity, components)
+    }
+}
+
+impl<C> AspectFilter<C> for Not<C>
+where
+    C: ComponentManager,
+{
+    #[inline]
+    fn check<'a>(&self, entity: EntityData<'a, C>, components: &C) -> bool {
+        !self.0.check(entity, components)
+    }
+}
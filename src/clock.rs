@@ -0,0 +1,207 @@
+//! The time seen by timed systems.
+//!
+//! Every world has a `Clock` in `data.clock`, which timed interval systems
+//! and fixed timestep systems read instead of the system time. The clock
+//! samples its time source once at the start of each `World::update`, so
+//! every system in an update sees the same time.
+//!
+//! The clock can be paused or sped up and slowed down, which affects all of
+//! the timed systems in the world at once. For tests and replays, give it a
+//! `ManualClock` and move time forward by hand.
+//!
+//! ```
+//! # #[macro_use] extern crate conniecs_derive; extern crate conniecs;
+//! use conniecs::clock::ManualClock;
+//! # #[derive(ComponentManager)] struct Components;
+//! # #[derive(ServiceManager, Default)] struct Services {}
+//! # #[derive(SystemManager)] struct Systems {}
+//! # fn main() {
+//! let time = ManualClock::new();
+//! let mut world = conniecs::World::<Systems>::new();
+//! world.data.clock.set_source(time.clone());
+//! // The first update starts the clock
+//! world.update();
+//!
+//! time.advance(16_000_000);
+//! world.update();
+//! assert_eq!(world.data.clock.now(), 16_000_000);
+//!
+//! world.data.clock.set_scale(0.5);
+//! time.advance(16_000_000);
+//! world.update();
+//! assert_eq!(world.data.clock.now(), 24_000_000);
+//! # }
+//! ```
+
+use time;
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Somewhere to read the current time from, in nanoseconds. Only the
+/// differences between readings matter.
+pub trait TimeSource: Send + Sync + 'static {
+    fn now(&self) -> u64;
+}
+
+impl<F> TimeSource for F
+where
+    F: Fn() -> u64 + Send + Sync + 'static,
+{
+    fn now(&self) -> u64 {
+        self()
+    }
+}
+
+/// Reads the system's monotonic clock.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemClock;
+
+impl TimeSource for SystemClock {
+    fn now(&self) -> u64 {
+        time::precise_time_ns()
+    }
+}
+
+/// A time source which only moves when told to. Clones share the same time,
+/// so keep one to drive the clock of a world.
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock(Arc<AtomicU64>);
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn advance(&self, ns: u64) {
+        self.0.fetch_add(ns, Ordering::Relaxed);
+    }
+
+    pub fn set(&self, ns: u64) {
+        self.0.store(ns, Ordering::Relaxed);
+    }
+}
+
+impl TimeSource for ManualClock {
+    fn now(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The world's view of time, in nanoseconds since it first sampled its
+/// source.
+pub struct Clock {
+    source: Box<dyn TimeSource>,
+    last: Option<u64>,
+    now: u64,
+    remainder: f64,
+    scale: f64,
+    paused: bool,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock::new()
+    }
+}
+
+impl Clock {
+    /// A clock following the system time.
+    pub fn new() -> Self {
+        Clock::with_source(SystemClock)
+    }
+
+    pub fn with_source<S>(source: S) -> Self
+    where
+        S: TimeSource,
+    {
+        Clock {
+            source: Box::new(source),
+            last: None,
+            now: 0,
+            remainder: 0.0,
+            scale: 1.0,
+            paused: false,
+        }
+    }
+
+    /// Switches to a different time source. Time carries on from where it
+    /// was, counting from the new source's next reading.
+    pub fn set_source<S>(&mut self, source: S)
+    where
+        S: TimeSource,
+    {
+        self.source = Box::new(source);
+        self.last = None;
+    }
+
+    /// The time as of the last sample.
+    #[inline]
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Sets how fast time passes, where 1 is normal speed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scale is negative or not finite.
+    pub fn set_scale(&mut self, scale: f64) {
+        assert!(
+            scale >= 0.0 && scale.is_finite(),
+            "Time can't be scaled by {}",
+            scale
+        );
+        self.scale = scale;
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Reads the time source and moves the clock forward by however much
+    /// time passed, scaled. Time which passes while paused is skipped. Called
+    /// by the world at the start of every update.
+    pub fn sample(&mut self) {
+        let raw = self.source.now();
+        let elapsed = match self.last.replace(raw) {
+            Some(last) => raw.saturating_sub(last),
+            None => 0,
+        };
+        if self.paused {
+            return;
+        }
+
+        if self.scale == 1.0 {
+            self.now += elapsed;
+        } else {
+            let scaled = elapsed as f64 * self.scale + self.remainder;
+            self.now += scaled as u64;
+            self.remainder = scaled.fract();
+        }
+    }
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Clock")
+            .field("now", &self.now)
+            .field("scale", &self.scale)
+            .field("paused", &self.paused)
+            .finish()
+    }
+}
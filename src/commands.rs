@@ -0,0 +1,237 @@
+//! Structural edits recorded during system processing.
+//!
+//! Systems iterating over entities can't create, remove or change the
+//! components of entities without invalidating what they are iterating over.
+//! Instead they record those edits in `data.commands`, which holds them as
+//! boxed closures in the order they were queued.
+//!
+//! The buffer is applied whenever the world flushes its queues, which
+//! `World::update` does before and after running the systems. Edits are
+//! applied in order, and then the systems are told about every entity which
+//! was created, changed or removed, the same as for edits made directly
+//! through the `DataHelper`.
+//!
+//! ```
+//! # #[macro_use] extern crate conniecs_derive; extern crate conniecs;
+//! # #[derive(ComponentManager)] struct Components {
+//! # #[hot] pub health: conniecs::ComponentList<Components, i32>,
+//! # #[storage(cold)] pub dead: conniecs::ComponentList<Components, ()>, }
+//! # #[derive(ServiceManager, Default)] struct Services {}
+//! # type DataHelper = conniecs::DataHelper<Components, Services>;
+//! # type EntityIter<'a> = conniecs::EntityIter<'a, Components>;
+//! #[derive(Default, System)]
+//! #[system_type(entity)]
+//! #[aspect(all(health), none(dead))]
+//! #[process(reap)]
+//! struct Reaper;
+//!
+//! fn reap(_: &mut Reaper, entities: EntityIter, data: &mut DataHelper) {
+//!     for entity in entities {
+//!         if data.components.health[entity] <= 0 {
+//!             data.commands.insert(**entity, |c| &mut c.dead, ());
+//!         }
+//!     }
+//! }
+//! # #[derive(SystemManager)] struct Systems {
+//! # reaper: conniecs::system::EntitySystem<Reaper>, }
+//! # fn main() {
+//! # let mut world = conniecs::World::<Systems>::new();
+//! # let e = world.data.create_entity(|e, c, _| { c.health.add(e, 0); });
+//! # world.update();
+//! # world.data.with_entity_data(e, |e, c, _| assert!(c.dead.has(e)));
+//! # }
+//! ```
+
+use std::fmt;
+use std::mem;
+
+use crate::component::{Component, ComponentList, ComponentManager};
+use crate::entity::{BuildData, Entity, EntityBuilder, EntityManager, EntityModifier, ModifyData};
+use crate::services::ServiceManager;
+
+type BoxedBuilder<C, M> = Box<dyn FnOnce(BuildData<C>, &mut C, &mut M) + Send>;
+type BoxedModifier<C, M> = Box<dyn FnOnce(ModifyData<C>, &mut C, &mut M) + Send>;
+
+enum Command<C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    Create(BoxedBuilder<C, M>),
+    Remove(Entity),
+    Modify(Entity, BoxedModifier<C, M>),
+    SetParent(Entity, Entity),
+    ClearParent(Entity),
+}
+
+/// A buffer of structural edits to the world, recorded while systems are
+/// processing and applied the next time the world flushes its queues.
+///
+/// Every entity touched by `insert`, `remove` or `modify_entity` is
+/// reactivated in the systems exactly once per flush, no matter how many
+/// edits were queued against it or made through `DataHelper::modify_entity`.
+/// Entities created since the last flush are only activated, with the edits
+/// already applied. Edits queued against entities which have been removed by
+/// the time the buffer is applied are ignored.
+///
+/// Everything captured by a queued edit must be `Send`, so that the world
+/// can still be moved between threads.
+pub struct Commands<C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    queue: Vec<Command<C, M>>,
+}
+
+impl<C, M> Default for Commands<C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    fn default() -> Self {
+        Commands::new()
+    }
+}
+
+impl<C, M> Commands<C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    pub fn new() -> Self {
+        Commands { queue: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Moves everything queued in `other` onto the end of this buffer.
+    pub(crate) fn append(&mut self, mut other: Commands<C, M>) {
+        self.queue.append(&mut other.queue);
+    }
+
+    /// Queues the creation of a new entity.
+    pub fn create_entity<F>(&mut self, builder: F)
+    where
+        F: FnOnce(BuildData<C>, &mut C, &mut M) + Send + 'static,
+    {
+        self.queue.push(Command::Create(Box::new(builder)));
+    }
+
+    pub fn create_entity_with_builder<B>(&mut self, builder: B)
+    where
+        B: EntityBuilder<C, M> + Send + 'static,
+    {
+        self.create_entity(move |e, c, s| builder.build(e, c, s));
+    }
+
+    /// Queues the removal of an entity.
+    pub fn remove_entity(&mut self, entity: Entity) {
+        self.queue.push(Command::Remove(entity));
+    }
+
+    pub fn modify_entity<F>(&mut self, entity: Entity, modifier: F)
+    where
+        F: FnOnce(ModifyData<C>, &mut C, &mut M) + Send + 'static,
+    {
+        self.queue.push(Command::Modify(entity, Box::new(modifier)));
+    }
+
+    pub fn modify_entity_with_modifier<E>(&mut self, entity: Entity, modifier: E)
+    where
+        E: EntityModifier<C, M> + Send + 'static,
+    {
+        self.modify_entity(entity, move |e, c, s| modifier.modify(e, c, s));
+    }
+
+    /// Queues adding (or replacing) a component on an entity. `list` picks
+    /// the component list out of the manager, e.g. `|c| &mut c.position`.
+    pub fn insert<T, L>(&mut self, entity: Entity, list: L, component: T)
+    where
+        T: Component + Send,
+        L: FnOnce(&mut C) -> &mut ComponentList<C, T> + Send + 'static,
+    {
+        self.modify_entity(entity, move |e, c, _| {
+            list(c).set(e, component);
+        });
+    }
+
+    /// Queues removing a component from an entity, if it has one.
+    pub fn remove<T, L>(&mut self, entity: Entity, list: L)
+    where
+        T: Component,
+        L: FnOnce(&mut C) -> &mut ComponentList<C, T> + Send + 'static,
+    {
+        self.modify_entity(entity, move |e, c, _| {
+            list(c).remove(e);
+        });
+    }
+
+    /// Queues making `parent` the parent of `child`. Ignored if it would be
+    /// refused by `DataHelper::set_parent` when the buffer is applied.
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) {
+        self.queue.push(Command::SetParent(child, parent));
+    }
+
+    /// Queues detaching an entity from its parent.
+    pub fn clear_parent(&mut self, child: Entity) {
+        self.queue.push(Command::ClearParent(child));
+    }
+
+    /// Applies everything queued to the entities and components. The systems
+    /// are told about the changes when the entity manager flushes its own
+    /// queue afterwards.
+    pub(crate) fn flush(
+        &mut self,
+        entities: &mut EntityManager<C>,
+        components: &mut C,
+        services: &mut M,
+    ) {
+        if self.queue.is_empty() {
+            return;
+        }
+
+        let mut queue = mem::take(&mut self.queue);
+
+        for command in queue.drain(..) {
+            match command {
+                Command::Create(builder) => {
+                    entities.create_entity(builder, components, services);
+                }
+                Command::Remove(entity) => {
+                    entities.remove_entity(entity);
+                }
+                Command::Modify(entity, modifier) => {
+                    entities.modify_entity(entity, modifier, components, services);
+                }
+                Command::SetParent(child, parent) => {
+                    entities.set_parent(child, parent);
+                }
+                Command::ClearParent(child) => {
+                    entities.clear_parent(child);
+                }
+            }
+        }
+
+        // Hang on to the allocation for next time
+        self.queue = queue;
+    }
+}
+
+impl<C, M> fmt::Debug for Commands<C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Commands")
+            .field("len", &self.queue.len())
+            .finish()
+    }
+}
@@ -1,14 +1,22 @@
 //! TODO: Add documentation including describing how the derive macros work
 
-use fnv::FnvHashMap;
-use vec_map::VecMap;
-
-use std::collections::HashMap;
+use std::any::Any;
 use std::marker::PhantomData;
 use std::ops::{Index, IndexMut};
 
-use self::InnerComponentList::{Cold, Hot};
-use crate::entity::{BuildData, EditData, IndexedEntity, ModifyData};
+use crate::entity::{BuildData, EditData, Entity, IndexedEntity, ModifyData};
+use crate::storage::{
+    ComponentStorage, DenseVecStorage, FlagStorage, HashMapStorage, InnerComponentList,
+    VecMapStorage,
+};
+use crate::tracking::{Change, ChangeTracker, Changes, Tick};
+
+pub use crate::storage::{Iter, IterMut};
+
+/// A bitset of which component lists an entity has a component in. Each list
+/// in a derived `ComponentManager` is assigned one bit, in declaration order,
+/// for as many lists as there are bits.
+pub type Signature = u64;
 
 /// Marks types which are suitable for being components. It is implemented for all
 /// types which are `'static`.
@@ -19,12 +27,37 @@ impl<T: 'static> Component for T {}
 /// component lists. You should not try to implement this manually. Use
 /// `#[derive(Components)]` instead. See the module documentation for more
 /// information.
-pub trait ComponentManager: 'static {
+pub trait ComponentManager: Sized + 'static {
     fn build_manager() -> Self;
 
+    #[doc(hidden)]
+    fn __remove_all(&mut self, entity: &IndexedEntity<Self>);
+
     #[doc(hidden)]
     fn __wipe_all(&mut self);
 
+    /// Sets the signature bits of an entity for every list it has a
+    /// component in, for entities whose components were put in place
+    /// without going through the lists.
+    #[doc(hidden)]
+    fn __sync_signature(&self, entity: &IndexedEntity<Self>);
+
+    #[doc(hidden)]
+    fn __set_tick(&mut self, tick: Tick);
+
+    /// Points at the list in the field with the given name, without creating
+    /// a reference to the rest of the manager. `this` must be valid.
+    #[doc(hidden)]
+    unsafe fn __list_ptr(this: *mut Self, name: &str) -> Option<*mut dyn Any>;
+
+    /// The names of the lists, in the order of their signature bits.
+    #[doc(hidden)]
+    fn __list_names() -> &'static [&'static str];
+
+    /// Copies every `Clone` component of one entity onto another.
+    #[doc(hidden)]
+    fn __clone_onto(&mut self, from: &IndexedEntity<Self>, to: &IndexedEntity<Self>);
+
     #[doc(hidden)]
     fn __please_use_the_derive_attribute();
 }
@@ -36,53 +69,85 @@ where
     T: Component,
 {
     pub(crate) inner: InnerComponentList<T>,
+    bit: Signature,
+    tracker: Option<Box<ChangeTracker>>,
     _marker: PhantomData<C>,
 }
 
-#[derive(Debug)]
-pub(crate) enum InnerComponentList<T>
-where
-    T: Component,
-{
-    Hot(VecMap<T>),
-    Cold(FnvHashMap<usize, T>),
-}
-
 impl<C, T> ComponentList<C, T>
 where
     C: ComponentManager,
     T: Component,
 {
-    pub fn hot() -> Self {
+    fn from_inner(inner: InnerComponentList<T>) -> Self {
         ComponentList {
-            inner: Hot(VecMap::new()),
+            inner,
+            bit: 0,
+            tracker: None,
             _marker: PhantomData,
         }
     }
 
+    pub fn hot() -> Self {
+        ComponentList::from_inner(InnerComponentList::Hot(VecMapStorage::default()))
+    }
+
     pub fn cold() -> Self {
-        ComponentList {
-            inner: Cold(HashMap::with_hasher(Default::default())),
-            _marker: PhantomData,
-        }
+        ComponentList::from_inner(InnerComponentList::Cold(HashMapStorage::default()))
+    }
+
+    pub fn dense() -> Self {
+        ComponentList::from_inner(InnerComponentList::Dense(DenseVecStorage::default()))
+    }
+
+    /// Only for zero-sized `T`. Anything else fails to compile.
+    pub fn flag() -> Self {
+        ComponentList::from_inner(InnerComponentList::Flag(FlagStorage::default()))
+    }
+
+    /// Creates a list backed by your own storage.
+    pub fn with_storage<S>(storage: S) -> Self
+    where
+        S: ComponentStorage<T> + Send + Sync,
+    {
+        ComponentList::from_inner(InnerComponentList::Custom(Box::new(storage)))
+    }
+
+    /// Makes the list record changes to its components. See the `tracking`
+    /// module for details.
+    pub fn track_changes(mut self) -> Self {
+        self.tracker = Some(Default::default());
+        self
     }
 
     pub fn add(&mut self, entity: BuildData<C>, component: T) -> Option<T> {
-        self.inner.insert(entity.0.index(), component)
+        entity.0.add_signature(self.bit);
+        let result = self.inner.insert(**entity.0, component);
+        self.track(**entity.0, replaced_change(&result));
+        result
     }
 
     pub fn remove(&mut self, entity: ModifyData<C>) -> Option<T> {
-        self.inner.remove(entity.0.index())
+        entity.0.remove_signature(self.bit);
+        let result = self.inner.remove(entity.0.index());
+        if result.is_some() {
+            self.track(**entity.0, Change::Removed);
+        }
+        result
     }
 
     pub fn set<E>(&mut self, entity: E, component: T) -> Option<T>
     where
         E: EditData<C>,
     {
-        let result = self.inner.insert(entity.entity().index(), component);
-        if result.is_none() && !E::can_insert_components() {
-            panic!("ComponentList::set was used to insert a new component when modification of activated components was not allowed");
+        let result = self.inner.insert(**entity.entity(), component);
+        if result.is_none() {
+            if !E::can_insert_components() {
+                panic!("ComponentList::set was used to insert a new component when modification of activated components was not allowed");
+            }
+            entity.entity().add_signature(self.bit);
         }
+        self.track(**entity.entity(), replaced_change(&result));
         result
     }
 
@@ -105,17 +170,205 @@ where
     where
         E: EditData<C>,
     {
+        if self.tracker.is_some() && self.inner.contains_key(entity.entity().index()) {
+            self.track(**entity.entity(), Change::Modified);
+        }
         self.inner.get_mut(entity.entity().index())
     }
 
+    /// The bit this list occupies in entity signatures, or 0 if it wasn't
+    /// assigned one.
+    #[inline]
+    pub fn signature_bit(&self) -> Signature {
+        self.bit
+    }
+
+    #[doc(hidden)]
+    pub fn __with_signature_bit(mut self, bit: u32) -> Self {
+        self.bit = 1 << bit;
+        self
+    }
+
+    /// Used by derived aspects. Tests the entity's signature when this list
+    /// has a bit, falling back on looking the entity up otherwise.
+    #[doc(hidden)]
+    #[inline]
+    pub fn __matches<E>(&self, entity: E, signature: Signature) -> bool
+    where
+        E: EditData<C>,
+    {
+        if self.bit != 0 {
+            signature & self.bit != 0
+        } else {
+            self.has(entity)
+        }
+    }
+
+    /// The number of entities with a component in this list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.len() == 0
+    }
+
+    /// The number of components the list can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Iterates over every component in the list along with the entity it
+    /// belongs to, in no particular order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.inner.iter()
+    }
+
+    /// On a tracked list, every component counts as modified.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.track_all(Change::Modified);
+        self.inner.iter_mut()
+    }
+
+    #[inline]
+    pub fn is_tracked(&self) -> bool {
+        self.tracker.is_some()
+    }
+
+    /// Iterates over the changes made since the start of the given tick.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the list isn't tracked.
+    pub fn changes_since(&self, tick: Tick) -> Changes<'_> {
+        self.tracker().changes_since(tick)
+    }
+
+    /// Forgets every recorded change.
+    pub fn clear_changes(&mut self) {
+        if let Some(tracker) = self.tracker.as_mut() {
+            tracker.clear();
+        }
+    }
+
+    /// Forgets the changes recorded before the given tick.
+    pub fn clear_changes_before(&mut self, tick: Tick) {
+        if let Some(tracker) = self.tracker.as_mut() {
+            tracker.clear_before(tick);
+        }
+    }
+
+    fn tracker(&self) -> &ChangeTracker {
+        self.tracker
+            .as_ref()
+            .expect("Changes were requested from a list which isn't tracked")
+    }
+
+    #[inline]
+    pub(crate) fn track(&mut self, entity: Entity, change: Change) {
+        if let Some(tracker) = self.tracker.as_mut() {
+            tracker.record(entity, change);
+        }
+    }
+
+    /// Records the same change for every component in the list.
+    pub(crate) fn track_all(&mut self, change: Change) {
+        if let Some(tracker) = self.tracker.as_mut() {
+            for (entity, _) in self.inner.iter() {
+                tracker.record(entity, change);
+            }
+        }
+    }
+
+    /// Removes every component from the list. The signatures of the entities
+    /// are not updated and they aren't reactivated, so this is only available
+    /// through `DataHelper::drain_components`, which takes care of both.
+    pub(crate) fn drain(&mut self) -> Vec<(Entity, T)> {
+        let drained = self.inner.drain();
+        for &(entity, _) in &drained {
+            self.track(entity, Change::Removed);
+        }
+        drained
+    }
+
     #[doc(hidden)]
     pub fn __clear(&mut self, entity: &IndexedEntity<C>) {
-        self.inner.remove(entity.index());
+        entity.remove_signature(self.bit);
+        if self.inner.remove(entity.index()).is_some() {
+            self.track(**entity, Change::Removed);
+        }
     }
 
     #[doc(hidden)]
     pub fn __wipe(&mut self) {
         self.inner.clear();
+        self.clear_changes();
+    }
+
+    #[doc(hidden)]
+    pub fn __sync_signature(&self, entity: &IndexedEntity<C>) {
+        if self.inner.contains_key(entity.index()) {
+            entity.add_signature(self.bit);
+        }
+    }
+
+    /// Inserts a component without touching the entity's signature or
+    /// recording a change.
+    #[doc(hidden)]
+    pub fn __load(&mut self, entity: Entity, component: T) {
+        self.inner.insert(entity, component);
+    }
+
+    #[doc(hidden)]
+    pub fn __set_tick(&mut self, tick: Tick) {
+        if let Some(tracker) = self.tracker.as_mut() {
+            tracker.set_tick(tick);
+        }
+    }
+}
+
+/// Cloning a list whose custom storage doesn't implement `try_clone` panics.
+impl<C, T> Clone for ComponentList<C, T>
+where
+    C: ComponentManager,
+    T: Component + Clone,
+{
+    fn clone(&self) -> Self {
+        ComponentList {
+            inner: self.inner.clone(),
+            bit: self.bit,
+            tracker: self.tracker.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, C, T> IntoIterator for &'a ComponentList<C, T>
+where
+    C: ComponentManager,
+    T: Component,
+{
+    type Item = (Entity, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, C, T> IntoIterator for &'a mut ComponentList<C, T>
+where
+    C: ComponentManager,
+    T: Component,
+{
+    type Item = (Entity, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
     }
 }
 
@@ -138,63 +391,65 @@ where
     E: EditData<C>,
 {
     fn index_mut(&mut self, entity: E) -> &mut T {
+        if self.tracker.is_some() && self.inner.contains_key(entity.entity().index()) {
+            self.track(**entity.entity(), Change::Modified);
+        }
         self.inner.index_mut(entity.entity().index())
     }
 }
 
-impl<T> InnerComponentList<T>
+/// Lets the derived `__clone_onto` copy components out of the lists whose
+/// type is `Clone` and skip the rest, by calling `__clone_onto` on a
+/// `&mut &mut __Cloner`. Method lookup tries `__CloneComponent` first, and
+/// only falls back on `__SkipComponent` when the component isn't `Clone`.
+#[doc(hidden)]
+pub struct __Cloner<'a, C, T>(pub &'a mut ComponentList<C, T>)
 where
-    T: Component,
-{
-    pub(crate) fn insert(&mut self, index: usize, component: T) -> Option<T> {
-        match *self {
-            Hot(ref mut map) => map.insert(index, component),
-            Cold(ref mut map) => map.insert(index, component),
-        }
-    }
+    C: ComponentManager,
+    T: Component;
 
-    pub(crate) fn remove(&mut self, index: usize) -> Option<T> {
-        match *self {
-            Hot(ref mut map) => map.remove(index),
-            Cold(ref mut map) => map.remove(&index),
-        }
-    }
+#[doc(hidden)]
+pub trait __CloneComponent<C>
+where
+    C: ComponentManager,
+{
+    fn __clone_onto(&mut self, from: &IndexedEntity<C>, to: &IndexedEntity<C>);
+}
 
-    pub(crate) fn contains_key(&self, index: usize) -> bool {
-        match *self {
-            Hot(ref map) => map.contains_key(index),
-            Cold(ref map) => map.contains_key(&index),
+impl<C, T> __CloneComponent<C> for &mut __Cloner<'_, C, T>
+where
+    C: ComponentManager,
+    T: Component + Clone,
+{
+    fn __clone_onto(&mut self, from: &IndexedEntity<C>, to: &IndexedEntity<C>) {
+        let list = &mut *self.0;
+        if let Some(component) = list.inner.get(from.index()).cloned() {
+            to.add_signature(list.bit);
+            let result = list.inner.insert(**to, component);
+            list.track(**to, replaced_change(&result));
         }
     }
+}
 
-    pub(crate) fn index(&self, index: usize) -> &T {
-        self.get(index)
-            .unwrap_or_else(|| panic!("Entity at index {} does not have this component attached"))
-    }
-
-    pub(crate) fn index_mut(&mut self, index: usize) -> &mut T {
-        self.get_mut(index)
-            .unwrap_or_else(|| panic!("Entity at index {} does not have this component attached"))
-    }
-
-    pub(crate) fn get(&self, index: usize) -> Option<&T> {
-        match *self {
-            Hot(ref map) => map.get(index),
-            Cold(ref map) => map.get(&index),
-        }
-    }
+#[doc(hidden)]
+pub trait __SkipComponent<C>
+where
+    C: ComponentManager,
+{
+    fn __clone_onto(&mut self, from: &IndexedEntity<C>, to: &IndexedEntity<C>);
+}
 
-    pub(crate) fn get_mut(&mut self, index: usize) -> Option<&mut T> {
-        match *self {
-            Hot(ref mut map) => map.get_mut(index),
-            Cold(ref mut map) => map.get_mut(&index),
-        }
-    }
+impl<C, T> __SkipComponent<C> for __Cloner<'_, C, T>
+where
+    C: ComponentManager,
+    T: Component,
+{
+    fn __clone_onto(&mut self, _: &IndexedEntity<C>, _: &IndexedEntity<C>) {}
+}
 
-    pub(crate) fn clear(&mut self) {
-        match *self {
-            Hot(ref mut map) => map.clear(),
-            Cold(ref mut map) => map.clear(),
-        }
+fn replaced_change<T>(replaced: &Option<T>) -> Change {
+    match replaced {
+        Some(_) => Change::Modified,
+        None => Change::Added,
     }
 }
@@ -0,0 +1,96 @@
+//! Groups of components which are added and removed together.
+//!
+//! `#[derive(Bundle)]` on a struct with named fields makes it an
+//! `EntityBuilder`, adding each field to the component list of the same name,
+//! and an `EntityModifier`, which sets each field on an existing entity. A
+//! field can be put in a list with a different name with
+//! `#[component(list_name)]`. The component manager defaults to
+//! `crate::Components`, and a different one can be given with
+//! `#[components(path::to::Components)]`.
+//!
+//! `RemoveBundle` is the opposite of the modifier, taking every component in
+//! the bundle off an entity.
+//!
+//! ```
+//! # #[macro_use] extern crate conniecs_derive; extern crate conniecs;
+//! use conniecs::entity::RemoveBundle;
+//! # #[derive(ComponentManager)] struct Components {
+//! #     #[hot] position: conniecs::ComponentList<Components, (f32, f32)>,
+//! #     #[hot] health: conniecs::ComponentList<Components, u32>,
+//! # }
+//! # #[derive(ServiceManager, Default)] struct Services {}
+//! # #[derive(SystemManager)] struct Systems {}
+//! #[derive(Bundle)]
+//! #[components(Components)]
+//! struct Player {
+//!     position: (f32, f32),
+//!     #[component(health)]
+//!     hp: u32,
+//! }
+//!
+//! # fn main() {
+//! let mut world = conniecs::World::<Systems>::new();
+//! let player = world.data.create_entity_with_builder(Player {
+//!     position: (0.0, 0.0),
+//!     hp: 10,
+//! });
+//! world.data.modify_entity_with_modifier(player, RemoveBundle::<Player>::new());
+//! assert_eq!(world.data.components.health.len(), 0);
+//! # }
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::component::ComponentManager;
+use crate::entity::{EntityModifier, ModifyData};
+use crate::services::ServiceManager;
+
+/// Implemented by `#[derive(Bundle)]`.
+pub trait Bundle<C>
+where
+    C: ComponentManager,
+{
+    /// Removes each of the bundle's components from an entity.
+    fn remove(entity: ModifyData<C>, components: &mut C);
+}
+
+/// A modifier which removes every component of the bundle `B`.
+pub struct RemoveBundle<B>(PhantomData<fn() -> B>);
+
+impl<B> RemoveBundle<B> {
+    pub fn new() -> Self {
+        RemoveBundle(PhantomData)
+    }
+}
+
+impl<B> Default for RemoveBundle<B> {
+    fn default() -> Self {
+        RemoveBundle::new()
+    }
+}
+
+impl<B> Clone for RemoveBundle<B> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<B> Copy for RemoveBundle<B> {}
+
+impl<B> fmt::Debug for RemoveBundle<B> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("RemoveBundle").finish()
+    }
+}
+
+impl<B, C, M> EntityModifier<C, M> for RemoveBundle<B>
+where
+    B: Bundle<C>,
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    fn modify(self, entity: ModifyData<C>, components: &mut C, _: &mut M) {
+        B::remove(entity, components)
+    }
+}
@@ -12,7 +12,7 @@ impl<'a, C: ComponentManager> Deref for EntityData<'a, C> {
     type Target = IndexedEntity<C>;
     #[inline]
     fn deref(&self) -> &IndexedEntity<C> {
-        &self.0
+        self.0
     }
 }
 
@@ -0,0 +1,179 @@
+//! Parent and child links between entities.
+//!
+//! Every world keeps a `Hierarchy`, read with `DataHelper::hierarchy` and
+//! changed with `DataHelper::set_parent` and `DataHelper::clear_parent`.
+//! Removing an entity removes everything below it too, when the world next
+//! flushes its queues.
+//!
+//! `Hierarchy::depth_first` walks every entity in the hierarchy with parents
+//! always before their children. Roots are visited in entity order and
+//! children in the order they were attached, so the order is the same every
+//! time, which suits systems that propagate transforms down the tree.
+//!
+//! ```
+//! # #[macro_use] extern crate conniecs_derive; extern crate conniecs;
+//! # #[derive(ComponentManager)] struct Components {
+//! #     #[hot] offset: conniecs::ComponentList<Components, f32>,
+//! #     #[hot] position: conniecs::ComponentList<Components, f32>,
+//! # }
+//! # #[derive(ServiceManager, Default)] struct Services {}
+//! # type DataHelper = conniecs::DataHelper<Components, Services>;
+//! #[derive(Default, System)]
+//! #[process(propagate)]
+//! struct Transforms;
+//!
+//! fn propagate(_: &mut Transforms, data: &mut DataHelper) {
+//!     let order: Vec<_> = data.hierarchy().depth_first().collect();
+//!     for entity in order {
+//!         let parent = data.hierarchy().parent(entity);
+//!         let base = parent
+//!             .and_then(|p| data.with_entity_data(p, |e, c, _| c.position[e]))
+//!             .unwrap_or(0.0);
+//!         data.with_entity_data(entity, |e, c, _| c.position[e] = base + c.offset[e]);
+//!     }
+//! }
+//! # #[derive(SystemManager)] struct Systems { transforms: Transforms }
+//! # fn main() {}
+//! ```
+
+use fnv::FnvHashMap;
+
+use crate::entity::Entity;
+
+#[derive(Clone, Debug, Default)]
+pub struct Hierarchy {
+    parents: FnvHashMap<Entity, Entity>,
+    children: FnvHashMap<Entity, Vec<Entity>>,
+}
+
+impl Hierarchy {
+    pub fn parent(&self, entity: Entity) -> Option<Entity> {
+        self.parents.get(&entity).copied()
+    }
+
+    /// The children of an entity, in the order they were attached.
+    pub fn children(&self, entity: Entity) -> &[Entity] {
+        self.children
+            .get(&entity)
+            .map_or(&[], |children| &children[..])
+    }
+
+    /// The parent of an entity, then its parent, and so on up to the root.
+    pub fn ancestors(&self, entity: Entity) -> Ancestors<'_> {
+        Ancestors {
+            hierarchy: self,
+            next: self.parent(entity),
+        }
+    }
+
+    /// How many ancestors an entity has.
+    pub fn depth(&self, entity: Entity) -> usize {
+        self.ancestors(entity).count()
+    }
+
+    /// Everything below an entity, depth first, not including the entity.
+    pub fn descendants(&self, entity: Entity) -> DepthFirst<'_> {
+        let mut walk = DepthFirst {
+            hierarchy: self,
+            stack: vec![entity],
+        };
+        walk.next();
+        walk
+    }
+
+    /// Entities which have children but no parent, in entity order.
+    pub fn roots(&self) -> Vec<Entity> {
+        let mut roots = self
+            .children
+            .keys()
+            .copied()
+            .filter(|entity| !self.parents.contains_key(entity))
+            .collect::<Vec<_>>();
+        roots.sort();
+        roots
+    }
+
+    /// Every entity with a parent or children, depth first from each root.
+    pub fn depth_first(&self) -> DepthFirst<'_> {
+        let mut stack = self.roots();
+        stack.reverse();
+        DepthFirst {
+            hierarchy: self,
+            stack,
+        }
+    }
+
+    /// Returns false, leaving the hierarchy unchanged, if the link would make
+    /// an entity its own ancestor.
+    pub(crate) fn set_parent(&mut self, child: Entity, parent: Entity) -> bool {
+        if child == parent || self.ancestors(parent).any(|e| e == child) {
+            return false;
+        }
+
+        self.clear_parent(child);
+        self.parents.insert(child, parent);
+        self.children.entry(parent).or_default().push(child);
+        true
+    }
+
+    pub(crate) fn clear_parent(&mut self, child: Entity) -> Option<Entity> {
+        let parent = self.parents.remove(&child)?;
+        let siblings = self.children.get_mut(&parent).unwrap();
+        siblings.retain(|&e| e != child);
+        if siblings.is_empty() {
+            self.children.remove(&parent);
+        }
+        Some(parent)
+    }
+
+    /// Takes an entity out of the hierarchy. Its children become roots.
+    pub(crate) fn remove(&mut self, entity: Entity) {
+        self.clear_parent(entity);
+        for child in self.children.remove(&entity).unwrap_or_default() {
+            self.parents.remove(&child);
+        }
+    }
+
+    /// Each child with its parent, parents before their children.
+    #[cfg(feature = "serde")]
+    pub(crate) fn links(&self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.depth_first()
+            .filter_map(move |child| Some((child, self.parent(child)?)))
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.parents.clear();
+        self.children.clear();
+    }
+}
+
+pub struct Ancestors<'a> {
+    hierarchy: &'a Hierarchy,
+    next: Option<Entity>,
+}
+
+impl Iterator for Ancestors<'_> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        let entity = self.next?;
+        self.next = self.hierarchy.parent(entity);
+        Some(entity)
+    }
+}
+
+pub struct DepthFirst<'a> {
+    hierarchy: &'a Hierarchy,
+    stack: Vec<Entity>,
+}
+
+impl Iterator for DepthFirst<'_> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        let entity = self.stack.pop()?;
+        let children = self.hierarchy.children(entity);
+        self.stack.extend(children.iter().rev());
+        Some(entity)
+    }
+}
@@ -4,11 +4,10 @@ use index_pool::iter::IndexIter;
 use vec_map::VecMap;
 
 use std::collections::btree_set::Iter as BIter;
-use std::collections::hash_map::Values;
 
 use crate::aspect::Aspect;
 use crate::component::ComponentManager;
-use crate::entity::{Entity, EntityData, IndexedEntity};
+use crate::entity::{EntityData, IndexedEntity};
 
 pub struct FilteredEntityIter<'a, C>
 where
@@ -24,7 +23,6 @@ pub enum EntityIter<'a, C>
 where
     C: ComponentManager,
 {
-    Map(Values<'a, Entity, IndexedEntity<C>>),
     Indexed(IndexedEntityIter<'a, C>),
     Watched(WatchedEntityIter<'a, C>),
 }
@@ -35,7 +33,6 @@ where
 {
     fn clone(&self) -> Self {
         match *self {
-            EntityIter::Map(ref map) => EntityIter::Map(map.clone()),
             EntityIter::Indexed(ref ind) => EntityIter::Indexed(ind.clone()),
             EntityIter::Watched(ref wat) => EntityIter::Watched(wat.clone()),
         }
@@ -57,7 +54,7 @@ where
     fn clone(&self) -> Self {
         IndexedEntityIter {
             iter: self.iter.clone(),
-            values: self.values.clone(),
+            values: self.values,
         }
     }
 }
@@ -91,8 +88,8 @@ where
     pub fn filter(self, aspect: Aspect<C>, components: &'a C) -> FilteredEntityIter<'a, C> {
         FilteredEntityIter {
             inner: self,
-            aspect: aspect,
-            components: components,
+            aspect,
+            components,
         }
     }
 }
@@ -104,7 +101,6 @@ where
     type Item = EntityData<'a, C>;
     fn next(&mut self) -> Option<EntityData<'a, C>> {
         match *self {
-            EntityIter::Map(ref mut values) => values.next().map(|x| EntityData(x)),
             EntityIter::Indexed(ref mut iter) => iter
                 .iter
                 .next()
@@ -0,0 +1,75 @@
+//! A stream of everything that happens to entities.
+//!
+//! Every time the world flushes its queues it publishes what happened to
+//! entities since the last flush, in the order it happened, to
+//! `DataHelper::entity_events`. The channel works like any other
+//! `EventChannel`, so code outside of systems can keep a `ReaderId` and read
+//! it after each update.
+//!
+//! Entities which are created also report a `ComponentAdded` for each
+//! component they were built with. Removed entities don't report their
+//! components being removed. Components are only reported for the first 64
+//! lists of the component manager, which are the ones with signature bits.
+//!
+//! ```
+//! # #[macro_use] extern crate conniecs_derive; extern crate conniecs;
+//! use conniecs::entity::EntityEvent;
+//! use conniecs::ReaderId;
+//! # #[derive(ComponentManager)] struct Components {
+//! #     #[hot] position: conniecs::ComponentList<Components, f32>,
+//! # }
+//! # #[derive(ServiceManager, Default)] struct Services {}
+//! # #[derive(SystemManager)] struct Systems {}
+//! # fn main() {
+//! let mut world = conniecs::World::<Systems>::new();
+//! let mut reader = ReaderId::default();
+//!
+//! let entity = world.data.create_entity(|e, c, _| {
+//!     c.position.add(e, 0.0);
+//! });
+//! world.update();
+//!
+//! let events: Vec<_> = world.data.entity_events().read(&mut reader).collect();
+//! assert_eq!(
+//!     events,
+//!     [
+//!         &EntityEvent::Created(entity),
+//!         &EntityEvent::ComponentAdded(entity, "position"),
+//!     ]
+//! );
+//! # }
+//! ```
+
+use crate::component::Signature;
+use crate::entity::Entity;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EntityEvent {
+    Created(Entity),
+    Removed(Entity),
+    /// The entity was changed by `modify_entity` or a queued command.
+    Modified(Entity),
+    /// A component was put in the named list.
+    ComponentAdded(Entity, &'static str),
+    /// A component was taken out of the named list.
+    ComponentRemoved(Entity, &'static str),
+}
+
+/// Pushes an event for each list whose signature bit differs between
+/// `before` and `after`.
+pub(crate) fn push_changes(
+    events: &mut Vec<EntityEvent>,
+    names: &'static [&'static str],
+    entity: Entity,
+    before: Signature,
+    after: Signature,
+) {
+    for (bit, &name) in names.iter().enumerate().take(64) {
+        let mask = 1 << bit;
+        if after & mask != 0 && before & mask == 0 {
+            events.push(EntityEvent::ComponentAdded(entity, name));
+        } else if before & mask != 0 && after & mask == 0 {
+            events.push(EntityEvent::ComponentRemoved(entity, name));
+        }
+    }
+}
@@ -1,19 +1,27 @@
+use fnv::{FnvHashMap, FnvHashSet};
 use index_pool::IndexPool;
 use vec_map::VecMap;
 
-use std::collections::hash_map::HashMap;
-use std::marker::PhantomData;
+use std::iter;
 use std::mem;
 
-use crate::component::ComponentManager;
+use crate::component::{ComponentManager, Signature};
+use crate::entity::hierarchy::Hierarchy;
 use crate::entity::iter::{EntityIter, IndexedEntityIter};
-use crate::entity::{BuildData, Entity, EntityBuilder, EntityData, Id, IndexedEntity};
+use crate::entity::lifecycle::{self, EntityEvent};
+use crate::entity::{
+    BuildData, Entity, EntityBuilder, EntityData, EntityModifier, Generation, IndexedEntity,
+    ModifyData,
+};
+#[cfg(feature = "serde")]
+use crate::serialize::SavedEntities;
 use crate::services::ServiceManager;
 use crate::system::SystemManager;
 
 enum Event {
-    BuildEntity(Entity),
-    RemoveEntity(Entity),
+    Build(Entity),
+    Modify(Entity),
+    Remove(Entity),
 }
 
 pub struct EntityManager<C>
@@ -22,9 +30,19 @@ where
 {
     indices: IndexPool,
     indexed_entities: VecMap<IndexedEntity<C>>,
-    entities: HashMap<Entity, IndexedEntity<C>>,
+    generations: Vec<Generation>,
     event_queue: Vec<Event>,
-    next_id: Id,
+    lifecycle: Vec<EntityEvent>,
+    hierarchy: Hierarchy,
+}
+
+impl<C> Default for EntityManager<C>
+where
+    C: ComponentManager,
+{
+    fn default() -> Self {
+        EntityManager::new()
+    }
 }
 
 impl<C> EntityManager<C>
@@ -35,9 +53,10 @@ where
         EntityManager {
             indices: IndexPool::new(),
             indexed_entities: VecMap::new(),
-            entities: HashMap::new(),
+            generations: Vec::new(),
             event_queue: Vec::new(),
-            next_id: 0,
+            lifecycle: Vec::new(),
+            hierarchy: Hierarchy::default(),
         }
     }
 
@@ -48,14 +67,45 @@ where
     {
         use self::Event::*;
 
-        let mut queue = mem::replace(&mut self.event_queue, Vec::new());
+        // Every edit has already been applied, so the systems only need to hear
+        // about each entity once. A freshly built entity is activated with
+        // its final components, which covers any later edits made to it.
+        let mut notified = FnvHashSet::default();
+        let mut queue = mem::take(&mut self.event_queue);
         for e in queue.drain(..) {
             match e {
-                BuildEntity(entity) => {
-                    systems.activated(EntityData(self.indexed(entity)), components, services);
+                Build(entity) => {
+                    // The entity may have been removed along with its parent
+                    if let Some(indexed) = self.get(entity) {
+                        notified.insert(entity);
+                        systems.activated(EntityData(indexed), components, services);
+                    }
                 }
-                RemoveEntity(entity) => {
-                    systems.deactivated(EntityData(self.indexed(entity)), components, services);
+                Modify(entity) => {
+                    // The entity may have been removed since it was modified
+                    if let Some(indexed) = self.get(entity) {
+                        if notified.insert(entity) {
+                            systems.reactivated(EntityData(indexed), components, services);
+                        }
+                    }
+                }
+                Remove(entity) => {
+                    // The whole subtree goes, parents before their children
+                    let subtree = iter::once(entity)
+                        .chain(self.hierarchy.descendants(entity))
+                        .collect::<Vec<_>>();
+                    for entity in subtree {
+                        // The same entity may have been queued for removal more than once
+                        let indexed = match self.get(entity) {
+                            Some(indexed) => indexed.__clone(),
+                            None => continue,
+                        };
+
+                        systems.deactivated(EntityData(&indexed), components, services);
+                        components.__remove_all(&indexed);
+                        self.remove(entity);
+                        self.lifecycle.push(EntityEvent::Removed(entity));
+                    }
                 }
             }
         }
@@ -76,20 +126,144 @@ where
     {
         let entity = self.create();
         builder.build(BuildData(self.indexed(entity)), components, services);
-        self.event_queue.push(Event::BuildEntity(entity));
+        self.event_queue.push(Event::Build(entity));
+        self.record_created(entity);
         entity
     }
 
+    /// Creates a copy of an entity with every `Clone` component it has.
+    /// Returns `None` if the entity isn't valid.
+    pub fn clone_entity<M>(
+        &mut self,
+        entity: Entity,
+        components: &mut C,
+        services: &mut M,
+    ) -> Option<Entity>
+    where
+        M: ServiceManager,
+    {
+        let from = self.get(entity)?.__clone();
+        let copy = self.create_entity(
+            |e: BuildData<C>, c: &mut C, _: &mut M| c.__clone_onto(&from, e.0),
+            components,
+            services,
+        );
+        Some(copy)
+    }
+
+    /// Copies an entity and all of its descendants, linking the copies up
+    /// the same way. The copy of `entity` has no parent.
+    pub fn clone_entity_tree<M>(
+        &mut self,
+        entity: Entity,
+        components: &mut C,
+        services: &mut M,
+    ) -> Option<Entity>
+    where
+        M: ServiceManager,
+    {
+        let root = self.clone_entity(entity, components, services)?;
+        let descendants = self.hierarchy.descendants(entity).collect::<Vec<_>>();
+        let mut copies = FnvHashMap::default();
+        copies.insert(entity, root);
+        for original in descendants {
+            // Parents come before their children, so a parent without a copy
+            // couldn't be cloned and its subtree is left out
+            let parent = self.hierarchy.parent(original);
+            let parent_copy = match parent.and_then(|parent| copies.get(&parent)) {
+                Some(&parent_copy) => parent_copy,
+                None => continue,
+            };
+            if let Some(copy) = self.clone_entity(original, components, services) {
+                self.hierarchy.set_parent(copy, parent_copy);
+                copies.insert(original, copy);
+            }
+        }
+        Some(root)
+    }
+
+    pub fn modify_entity<E, M>(
+        &mut self,
+        entity: Entity,
+        modifier: E,
+        components: &mut C,
+        services: &mut M,
+    ) -> bool
+    where
+        E: EntityModifier<C, M>,
+        M: ServiceManager,
+    {
+        let before = match self.get(entity) {
+            Some(indexed) => {
+                let before = indexed.signature();
+                modifier.modify(ModifyData(indexed), components, services);
+                before
+            }
+            None => return false,
+        };
+        self.event_queue.push(Event::Modify(entity));
+        self.record_modified(entity, before);
+        true
+    }
+
+    /// Clears a signature bit from every entity given and queues them to be
+    /// reactivated, for after their components were removed in bulk.
+    pub(crate) fn components_removed<I>(&mut self, entities: I, bit: Signature)
+    where
+        I: IntoIterator<Item = Entity>,
+    {
+        for entity in entities {
+            if let Some(indexed) = self.get(entity) {
+                let before = indexed.signature();
+                indexed.remove_signature(bit);
+                self.event_queue.push(Event::Modify(entity));
+                lifecycle::push_changes(
+                    &mut self.lifecycle,
+                    C::__list_names(),
+                    entity,
+                    before,
+                    before & !bit,
+                );
+            }
+        }
+    }
+
+    /// Records the creation of an entity and the components it has.
+    pub(crate) fn record_created(&mut self, entity: Entity) {
+        let signature = self.indexed(entity).signature();
+        self.lifecycle.push(EntityEvent::Created(entity));
+        lifecycle::push_changes(&mut self.lifecycle, C::__list_names(), entity, 0, signature);
+    }
+
+    /// Records a modification of an entity, given its signature from before.
+    pub(crate) fn record_modified(&mut self, entity: Entity, before: Signature) {
+        let after = self.indexed(entity).signature();
+        self.lifecycle.push(EntityEvent::Modified(entity));
+        lifecycle::push_changes(
+            &mut self.lifecycle,
+            C::__list_names(),
+            entity,
+            before,
+            after,
+        );
+    }
+
+    /// Takes the lifecycle events recorded since this was last called.
+    pub(crate) fn drain_lifecycle(&mut self) -> impl Iterator<Item = EntityEvent> + '_ {
+        self.lifecycle.drain(..)
+    }
+
+    /// Queues an entity and all of its descendants to be removed.
     pub fn remove_entity(&mut self, entity: Entity) -> bool {
-        if self.entities.contains_key(&entity) {
-            self.event_queue.push(Event::RemoveEntity(entity));
+        if self.is_valid(entity) {
+            self.event_queue.push(Event::Remove(entity));
             true
         } else {
             false
         }
     }
 
-    pub fn iter(&self) -> EntityIter<C> {
+    pub fn iter(&self) -> EntityIter<'_, C> {
         EntityIter::Indexed(IndexedEntityIter {
             iter: self.indices.all_indices(),
             values: &self.indexed_entities,
@@ -97,42 +271,163 @@ where
     }
 
     pub fn count(&self) -> usize {
-        self.indices.maximum()
+        self.indices.in_use()
     }
 
     pub fn indexed(&self, entity: Entity) -> &IndexedEntity<C> {
-        &self.entities[&entity]
+        self.get(entity)
+            .unwrap_or_else(|| panic!("{:?} is not a valid entity", entity))
+    }
+
+    /// Looks up the `IndexedEntity` for a handle, returning `None` if the
+    /// entity has been removed.
+    #[inline]
+    pub fn get(&self, entity: Entity) -> Option<&IndexedEntity<C>> {
+        match self.indexed_entities.get(entity.index()) {
+            Some(indexed) if indexed.entity == entity => Some(indexed),
+            _ => None,
+        }
     }
 
     /// Creates a new `Entity`, assigning it the first available index.
     pub fn create(&mut self) -> Entity {
-        self.next_id += 1;
-        let entity = Entity { id: self.next_id };
-        let ie = IndexedEntity {
-            index: self.indices.new_id(),
-            entity,
-            _marker: PhantomData,
+        let index = self.indices.new_id();
+        if index >= self.generations.len() {
+            self.generations.resize(index + 1, 0);
+        }
+
+        // Generation 0 is reserved for `Entity::nil()`, so skip it if we wrap.
+        let generation = &mut self.generations[index];
+        *generation = generation.wrapping_add(1).max(1);
+
+        let entity = Entity {
+            index: index as u32,
+            generation: *generation,
         };
-        self.indexed_entities.insert(ie.index, ie.__clone());
-        self.entities.insert(entity, ie);
+        self.indexed_entities
+            .insert(index, IndexedEntity::new(index, entity));
         entity
     }
 
     /// Returns true if an entity is valid (not removed from the manager).
     #[inline]
     pub fn is_valid(&self, entity: Entity) -> bool {
-        self.entities.contains_key(&entity)
+        self.get(entity).is_some()
     }
 
     /// Deletes an entity from the manager.
     pub fn remove(&mut self, entity: Entity) {
-        self.entities
-            .remove(&entity)
-            .map(|e| self.indices.return_id(e.index()));
+        if self.is_valid(entity) {
+            self.indexed_entities.remove(entity.index());
+            self.indices.return_id(entity.index()).ok();
+            self.hierarchy.remove(entity);
+        }
+    }
+
+    /// Copies the entities and allocation state, leaving out queued events.
+    pub(crate) fn snapshot(&self) -> Self {
+        let mut indices = IndexPool::new();
+        for index in self.indexed_entities.keys() {
+            indices.request_id(index).ok();
+        }
+        EntityManager {
+            indices,
+            indexed_entities: self
+                .indexed_entities
+                .iter()
+                .map(|(index, entity)| (index, entity.__clone()))
+                .collect(),
+            generations: self.generations.clone(),
+            event_queue: Vec::new(),
+            lifecycle: Vec::new(),
+            hierarchy: self.hierarchy.clone(),
+        }
+    }
+
+    /// Iterates over the entities which the systems have been told about,
+    /// skipping those whose creation hasn't been flushed yet.
+    pub(crate) fn iter_activated(&self) -> impl Iterator<Item = EntityData<'_, C>> {
+        let pending: FnvHashSet<Entity> = self
+            .event_queue
+            .iter()
+            .filter_map(|event| match *event {
+                Event::Build(entity) => Some(entity),
+                _ => None,
+            })
+            .collect();
+        // EntityIter has an inherent `filter` for aspects
+        Iterator::filter(self.iter(), move |entity| !pending.contains(&***entity))
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn save(&self) -> SavedEntities {
+        SavedEntities {
+            generations: self.generations.clone(),
+            entities: self.indexed_entities.values().map(|e| e.entity).collect(),
+            parents: self.hierarchy.links().collect(),
+        }
+    }
+
+    /// Rebuilds a manager from a save, checking that it is consistent.
+    #[cfg(feature = "serde")]
+    pub(crate) fn load(saved: SavedEntities) -> Result<Self, String> {
+        let mut manager = EntityManager::new();
+        manager.generations = saved.generations;
+        for entity in saved.entities {
+            let index = entity.index();
+            if entity.generation == 0 || manager.generations.get(index) != Some(&entity.generation)
+            {
+                return Err(format!("{:?} does not match the saved generations", entity));
+            }
+            if manager.indices.request_id(index).is_err() {
+                return Err(format!("{:?} was saved more than once", entity));
+            }
+            manager
+                .indexed_entities
+                .insert(index, IndexedEntity::new(index, entity));
+        }
+        for (child, parent) in saved.parents {
+            if !manager.set_parent(child, parent) {
+                return Err(format!("{:?} can't be a child of {:?}", child, parent));
+            }
+        }
+        Ok(manager)
+    }
+
+    pub fn hierarchy(&self) -> &Hierarchy {
+        &self.hierarchy
+    }
+
+    /// Makes `parent` the parent of `child`, replacing any parent it had.
+    /// Returns false if either entity isn't valid, `child` is an ancestor of
+    /// `parent`, or `parent` is queued to be removed.
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) -> bool {
+        self.is_valid(child)
+            && self.is_valid(parent)
+            && !self.removal_pending(parent)
+            && self.hierarchy.set_parent(child, parent)
+    }
+
+    /// Returns true if the entity or one of its ancestors is queued to be
+    /// removed.
+    fn removal_pending(&self, entity: Entity) -> bool {
+        let doomed = iter::once(entity)
+            .chain(self.hierarchy.ancestors(entity))
+            .collect::<Vec<_>>();
+        self.event_queue.iter().any(|event| match *event {
+            Event::Remove(removed) => doomed.contains(&removed),
+            _ => false,
+        })
+    }
+
+    /// Detaches an entity from its parent, returning the parent it had.
+    pub fn clear_parent(&mut self, child: Entity) -> Option<Entity> {
+        self.hierarchy.clear_parent(child)
     }
 
     pub fn clear(&mut self) {
-        self.entities.clear();
+        self.indexed_entities.clear();
         self.indices = IndexPool::new();
+        self.hierarchy.clear();
     }
 }
@@ -1,38 +1,93 @@
 //! TODO: Add documentation including describing how the derive macros work
 
+use std::cmp::Ordering;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 use std::ops::Deref;
+use std::sync::atomic::{self, AtomicU64};
 
-use crate::component::ComponentManager;
+use crate::component::{ComponentManager, Signature};
 
 pub use crate::entity::builder::*;
+pub use crate::entity::bundle::{Bundle, RemoveBundle};
 pub use crate::entity::data::*;
+pub use crate::entity::hierarchy::Hierarchy;
 pub use crate::entity::iter::*;
+pub use crate::entity::lifecycle::EntityEvent;
 pub use crate::entity::manager::*;
+pub use crate::entity::prefab::Prefab;
 
 pub mod builder;
+pub mod bundle;
 pub mod data;
+pub mod hierarchy;
 pub mod iter;
+pub mod lifecycle;
 pub mod manager;
+pub mod prefab;
 
 pub type Id = u64;
+pub type Generation = u32;
 
+/// A handle to an entity. It is made up of the index of the slot the entity
+/// occupies and the generation of that slot, so a handle kept around after its
+/// entity was removed will never resolve to whichever entity reuses the slot.
 #[derive(Default, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Entity {
-    id: Id,
+    index: u32,
+    generation: Generation,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct IndexedEntity<C>
 where
     C: ComponentManager,
 {
     index: usize,
     entity: Entity,
+    signature: AtomicU64,
     _marker: PhantomData<C>,
 }
 
+impl<C> PartialEq for IndexedEntity<C>
+where
+    C: ComponentManager,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.entity == other.entity
+    }
+}
+
+impl<C> Eq for IndexedEntity<C> where C: ComponentManager {}
+
+impl<C> PartialOrd for IndexedEntity<C>
+where
+    C: ComponentManager,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C> Ord for IndexedEntity<C>
+where
+    C: ComponentManager,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.entity.cmp(&other.entity)
+    }
+}
+
+impl<C> Hash for IndexedEntity<C>
+where
+    C: ComponentManager,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.entity.hash(state)
+    }
+}
+
 impl<C> fmt::Debug for IndexedEntity<C>
 where
     C: ComponentManager,
@@ -41,6 +96,7 @@ where
         fmt.debug_struct("IndexedEntity")
             .field("index", &self.index)
             .field("entity", &self.entity)
+            .field("signature", &self.signature())
             .finish()
     }
 }
@@ -51,9 +107,21 @@ impl Entity {
         Default::default()
     }
 
+    /// A unique id for this entity, packing the generation into the upper
+    /// 32 bits and the index into the lower 32 bits.
     #[inline]
     pub fn id(self) -> Id {
-        self.id
+        (Id::from(self.generation) << 32) | Id::from(self.index)
+    }
+
+    #[inline]
+    pub fn index(self) -> usize {
+        self.index as usize
+    }
+
+    #[inline]
+    pub fn generation(self) -> Generation {
+        self.generation
     }
 }
 
@@ -66,12 +134,45 @@ where
         self.index
     }
 
+    /// The bits of every component list this entity has a component in. This
+    /// is kept up to date by the component lists as components are added and
+    /// removed. Copies of the entity held by watchers are only brought up to
+    /// date when the world is flushed.
+    #[inline]
+    pub fn signature(&self) -> Signature {
+        self.signature.load(atomic::Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub(crate) fn add_signature(&self, bit: Signature) {
+        if bit != 0 {
+            self.signature.fetch_or(bit, atomic::Ordering::Relaxed);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn remove_signature(&self, bit: Signature) {
+        if bit != 0 {
+            self.signature.fetch_and(!bit, atomic::Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn new(index: usize, entity: Entity) -> Self {
+        IndexedEntity {
+            index,
+            entity,
+            signature: AtomicU64::new(0),
+            _marker: PhantomData,
+        }
+    }
+
     #[doc(hidden)]
     #[inline]
     pub fn __clone(&self) -> Self {
         IndexedEntity {
             index: self.index,
             entity: self.entity,
+            signature: AtomicU64::new(self.signature()),
             _marker: PhantomData,
         }
     }
@@ -0,0 +1,118 @@
+//! Templates for spawning entities, and copying existing ones.
+//!
+//! A `Prefab` holds an entity builder which can be used over and over. It
+//! can be made from a closure, or from any `EntityBuilder` which is `Clone`,
+//! such as a struct loaded from a data file. `DataHelper::instantiate` spawns
+//! copies of it, and a prefab can also be passed anywhere an `EntityBuilder`
+//! is taken, including to `Commands`.
+//!
+//! `DataHelper::clone_entity` copies an entity which already exists, with
+//! every component of a type which is `Clone`. Components which aren't
+//! `Clone` are left out, and components holding other entities still point
+//! at the same entities. `DataHelper::clone_entity_tree` copies the entity's
+//! descendants in the hierarchy as well.
+//!
+//! ```
+//! # #[macro_use] extern crate conniecs_derive; extern crate conniecs;
+//! use conniecs::entity::Prefab;
+//! # #[derive(ComponentManager)] struct Components {
+//! #     #[hot] health: conniecs::ComponentList<Components, u32>,
+//! # }
+//! # #[derive(ServiceManager, Default)] struct Services {}
+//! # #[derive(SystemManager)] struct Systems {}
+//! # fn main() {
+//! let goblin = Prefab::new(|e, c: &mut Components, _: &mut Services| {
+//!     c.health.add(e, 10);
+//! });
+//!
+//! let mut world = conniecs::World::<Systems>::new();
+//! let goblins = world.data.instantiate(&goblin, 3);
+//! let copy = world.data.clone_entity(goblins[0]).unwrap();
+//! world.update();
+//! assert_eq!(world.data.components.health.len(), 4);
+//! # let _ = copy;
+//! # }
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::component::ComponentManager;
+use crate::entity::{BuildData, EntityBuilder};
+use crate::services::ServiceManager;
+
+type BuildFn<C, M> = dyn Fn(BuildData<C>, &mut C, &mut M) + Send + Sync;
+
+/// A reusable entity builder. Cloning a prefab is cheap.
+pub struct Prefab<C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    build: Arc<BuildFn<C, M>>,
+}
+
+impl<C, M> Prefab<C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    pub fn new<F>(build: F) -> Self
+    where
+        F: Fn(BuildData<C>, &mut C, &mut M) + Send + Sync + 'static,
+    {
+        Prefab {
+            build: Arc::new(build),
+        }
+    }
+
+    /// Makes a prefab which builds a fresh clone of `builder` every time.
+    pub fn from_builder<B>(builder: B) -> Self
+    where
+        B: EntityBuilder<C, M> + Clone + Send + Sync + 'static,
+    {
+        Prefab::new(move |e, c, s| builder.clone().build(e, c, s))
+    }
+}
+
+impl<C, M> Clone for Prefab<C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    fn clone(&self) -> Self {
+        Prefab {
+            build: self.build.clone(),
+        }
+    }
+}
+
+impl<C, M> fmt::Debug for Prefab<C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Prefab").finish()
+    }
+}
+
+impl<C, M> EntityBuilder<C, M> for &Prefab<C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    fn build(self, entity: BuildData<C>, components: &mut C, services: &mut M) {
+        (self.build)(entity, components, services)
+    }
+}
+
+impl<C, M> EntityBuilder<C, M> for Prefab<C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    fn build(self, entity: BuildData<C>, components: &mut C, services: &mut M) {
+        (self.build)(entity, components, services)
+    }
+}
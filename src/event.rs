@@ -0,0 +1,190 @@
+//! Typed events passed between systems.
+//!
+//! An `EventChannel<T>` lives in the services, marked with `#[events]` so the
+//! world swaps its buffers at the start of every update. Events are kept for
+//! the update they were emitted in and the one after, so every system sees
+//! them whether it runs before or after the system which emitted them.
+//!
+//! Each reader keeps its own `ReaderId`, which remembers how far through the
+//! channel it has read, so no reader sees an event twice. A new reader starts
+//! with whatever is still buffered. Systems declare the channels they read
+//! with `#[read_events(...)]`, naming a `ReaderId` field and the service it
+//! reads from, which must share a name. Systems which declare their access
+//! with `#[reads]` or `#[writes]` also get a read of the channel's service.
+//!
+//! ```
+//! # #[macro_use] extern crate conniecs_derive; extern crate conniecs;
+//! use conniecs::{EventChannel, ReaderId};
+//! # #[derive(ComponentManager)] struct Components;
+//! # type DataHelper = conniecs::DataHelper<Components, Services>;
+//! pub struct Collision(u32);
+//!
+//! #[derive(Default, ServiceManager)]
+//! struct Services {
+//!     #[events]
+//!     collisions: EventChannel<Collision>,
+//! }
+//!
+//! #[derive(Default, System)]
+//! #[process(detect)]
+//! struct Detect;
+//!
+//! fn detect(_: &mut Detect, data: &mut DataHelper) {
+//!     data.services.collisions.emit(Collision(7));
+//! }
+//!
+//! #[derive(Default, System)]
+//! #[read_events(collisions)]
+//! #[process(damage)]
+//! struct Damage {
+//!     collisions: ReaderId<Collision>,
+//!     total: u32,
+//! }
+//!
+//! fn damage(damage: &mut Damage, data: &mut DataHelper) {
+//!     for collision in data.services.collisions.read(&mut damage.collisions) {
+//!         damage.total += collision.0;
+//!     }
+//! }
+//!
+//! #[derive(SystemManager)]
+//! struct Systems {
+//!     detect: Detect,
+//!     damage: Damage,
+//! }
+//!
+//! # fn main() {
+//! let mut world = conniecs::World::<Systems>::new();
+//! world.update();
+//! world.update();
+//! assert_eq!(world.systems.damage.total, 14);
+//! # }
+//! ```
+
+use std::fmt;
+use std::iter::Chain;
+use std::marker::PhantomData;
+use std::mem;
+use std::slice;
+
+pub type EventIter<'a, T> = Chain<slice::Iter<'a, T>, slice::Iter<'a, T>>;
+
+/// A double buffered queue of events.
+#[derive(Clone, Debug)]
+pub struct EventChannel<T> {
+    previous: Vec<T>,
+    current: Vec<T>,
+    /// The id of the first event in `previous`.
+    first: u64,
+}
+
+impl<T> Default for EventChannel<T> {
+    fn default() -> Self {
+        EventChannel::new()
+    }
+}
+
+impl<T> EventChannel<T> {
+    pub fn new() -> Self {
+        EventChannel {
+            previous: Vec::new(),
+            current: Vec::new(),
+            first: 0,
+        }
+    }
+
+    pub fn emit(&mut self, event: T) {
+        self.current.push(event);
+    }
+
+    pub fn emit_all<I>(&mut self, events: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.current.extend(events);
+    }
+
+    /// Returns the events the reader hasn't seen yet and moves it to the end
+    /// of the channel.
+    pub fn read<'a>(&'a self, reader: &mut ReaderId<T>) -> EventIter<'a, T> {
+        let skip = reader.next.saturating_sub(self.first) as usize;
+        reader.next = self.end();
+
+        let previous = &self.previous[skip.min(self.previous.len())..];
+        let skip = skip.saturating_sub(self.previous.len());
+        let current = &self.current[skip.min(self.current.len())..];
+        previous.iter().chain(current.iter())
+    }
+
+    /// A reader which will only see events emitted from now on.
+    pub fn reader_at_end(&self) -> ReaderId<T> {
+        ReaderId {
+            next: self.end(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Every buffered event, oldest first.
+    pub fn iter(&self) -> EventIter<'_, T> {
+        self.previous.iter().chain(self.current.iter())
+    }
+
+    /// The number of buffered events.
+    pub fn len(&self) -> usize {
+        self.previous.len() + self.current.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops the events from the last update, keeping the ones emitted
+    /// since. Done by the world at the start of every update for channels
+    /// marked `#[events]`.
+    pub fn swap(&mut self) {
+        self.first += self.previous.len() as u64;
+        mem::swap(&mut self.previous, &mut self.current);
+        self.current.clear();
+    }
+
+    fn end(&self) -> u64 {
+        self.first + self.len() as u64
+    }
+}
+
+/// How far through an `EventChannel` a reader has read.
+pub struct ReaderId<T> {
+    next: u64,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for ReaderId<T> {
+    fn default() -> Self {
+        ReaderId {
+            next: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for ReaderId<T> {
+    fn clone(&self) -> Self {
+        ReaderId {
+            next: self.next,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for ReaderId<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("ReaderId")
+            .field("next", &self.next)
+            .finish()
+    }
+}
+
+#[doc(hidden)]
+pub fn __check_reader<T>(reader: &mut ReaderId<T>, channel: &EventChannel<T>) {
+    let _ = (reader, channel);
+}
@@ -0,0 +1,262 @@
+//! Iterating several component lists at once.
+//!
+//! Tuples of component lists implement `Join`, yielding a tuple of references
+//! for every entity which has a component in all of them. Iteration is driven
+//! by whichever list holds the fewest components, and the rest are looked up
+//! by index.
+//!
+//! ```
+//! # #[macro_use] extern crate conniecs_derive; extern crate conniecs;
+//! use conniecs::Join;
+//! # #[derive(ComponentManager)] struct Components {
+//! # #[hot] pub position: conniecs::ComponentList<Components, f32>,
+//! # #[hot] pub velocity: conniecs::ComponentList<Components, f32>,
+//! # #[hot] pub drag: conniecs::ComponentList<Components, f32>, }
+//! # #[derive(ServiceManager, Default)] struct Services {}
+//! # type DataHelper = conniecs::DataHelper<Components, Services>;
+//!
+//! fn integrate(data: &mut DataHelper) {
+//!     let c = &mut data.components;
+//!     for (pos, vel, drag) in (&mut c.position, &c.velocity, c.drag.maybe()).join() {
+//!         *pos += vel * drag.cloned().unwrap_or(1.0);
+//!     }
+//! }
+//! # fn main() {}
+//! ```
+
+use std::vec;
+
+use crate::component::{Component, ComponentList, ComponentManager};
+use crate::tracking::Change;
+
+/// Implemented for component lists, `Maybe`s of them, and tuples of those.
+pub trait Join: Sized {
+    type Item;
+
+    /// Iterates over all of the entities which have every required component.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every list in the join is wrapped in `Maybe`, since there is
+    /// nothing to drive the iteration.
+    fn join(self) -> JoinIter<Self> {
+        let indices = self
+            .__indices()
+            .expect("A join needs at least one required component list");
+        JoinIter {
+            join: self,
+            indices: indices.into_iter(),
+        }
+    }
+
+    /// The number of components in the smallest required list, if any.
+    #[doc(hidden)]
+    fn __len(&self) -> Option<usize>;
+
+    /// The indices of the smallest required list, if any.
+    #[doc(hidden)]
+    fn __indices(&self) -> Option<Vec<usize>>;
+
+    /// Returns true if every required list has a component at the index.
+    #[doc(hidden)]
+    fn __contains(&self, index: usize) -> bool;
+
+    /// Fetches the item for an index, if every required list has it.
+    ///
+    /// # Safety
+    ///
+    /// Must not be called more than once with the same index, since mutable
+    /// joins hand out a mutable reference each time.
+    #[doc(hidden)]
+    unsafe fn __get(&mut self, index: usize) -> Option<Self::Item>;
+
+    /// Records the component at an index as modified in tracked lists which
+    /// are joined mutably. Called before the item is fetched, so that no
+    /// mutable reference into the list is alive while it's looked at.
+    #[doc(hidden)]
+    fn __track(&mut self, index: usize) {
+        let _ = index;
+    }
+}
+
+/// Makes a list optional within a join. Its item is `None` for entities
+/// without a component in the list. Created with `ComponentList::maybe`
+/// or `ComponentList::maybe_mut`.
+pub struct Maybe<J>(pub(crate) J);
+
+pub struct JoinIter<J>
+where
+    J: Join,
+{
+    join: J,
+    indices: vec::IntoIter<usize>,
+}
+
+impl<J> Iterator for JoinIter<J>
+where
+    J: Join,
+{
+    type Item = J::Item;
+
+    fn next(&mut self) -> Option<J::Item> {
+        for index in self.indices.by_ref() {
+            if self.join.__contains(index) {
+                self.join.__track(index);
+                // Every index comes from the keys of a single list, so they're unique
+                if let Some(item) = unsafe { self.join.__get(index) } {
+                    return Some(item);
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.indices.len()))
+    }
+}
+
+impl<C, T> ComponentList<C, T>
+where
+    C: ComponentManager,
+    T: Component,
+{
+    pub fn maybe(&self) -> Maybe<&Self> {
+        Maybe(self)
+    }
+
+    pub fn maybe_mut(&mut self) -> Maybe<&mut Self> {
+        Maybe(self)
+    }
+}
+
+impl<'a, C, T> Join for &'a ComponentList<C, T>
+where
+    C: ComponentManager,
+    T: Component,
+{
+    type Item = &'a T;
+
+    fn __len(&self) -> Option<usize> {
+        Some(self.inner.len())
+    }
+
+    fn __indices(&self) -> Option<Vec<usize>> {
+        Some(self.inner.keys())
+    }
+
+    fn __contains(&self, index: usize) -> bool {
+        self.inner.contains_key(index)
+    }
+
+    unsafe fn __get(&mut self, index: usize) -> Option<&'a T> {
+        let list = *self;
+        list.inner.get(index)
+    }
+}
+
+impl<'a, C, T> Join for &'a mut ComponentList<C, T>
+where
+    C: ComponentManager,
+    T: Component,
+{
+    type Item = &'a mut T;
+
+    fn __len(&self) -> Option<usize> {
+        Some(self.inner.len())
+    }
+
+    fn __indices(&self) -> Option<Vec<usize>> {
+        Some(self.inner.keys())
+    }
+
+    fn __contains(&self, index: usize) -> bool {
+        self.inner.contains_key(index)
+    }
+
+    unsafe fn __get(&mut self, index: usize) -> Option<&'a mut T> {
+        let list: *mut ComponentList<C, T> = &mut **self;
+        (*list).inner.get_mut(index)
+    }
+
+    fn __track(&mut self, index: usize) {
+        if self.is_tracked() {
+            if let Some(entity) = self.inner.entity(index) {
+                self.track(entity, Change::Modified);
+            }
+        }
+    }
+}
+
+impl<J> Join for Maybe<J>
+where
+    J: Join,
+{
+    type Item = Option<J::Item>;
+
+    fn __len(&self) -> Option<usize> {
+        None
+    }
+
+    fn __indices(&self) -> Option<Vec<usize>> {
+        None
+    }
+
+    fn __contains(&self, _: usize) -> bool {
+        true
+    }
+
+    unsafe fn __get(&mut self, index: usize) -> Option<Option<J::Item>> {
+        Some(self.0.__get(index))
+    }
+
+    fn __track(&mut self, index: usize) {
+        self.0.__track(index)
+    }
+}
+
+macro_rules! impl_join_tuple {
+    ($($name:ident $idx:tt),*) => {
+        impl<$($name),*> Join for ($($name,)*)
+        where
+            $($name: Join,)*
+        {
+            type Item = ($($name::Item,)*);
+
+            fn __len(&self) -> Option<usize> {
+                [$(self.$idx.__len()),*].iter().flatten().cloned().min()
+            }
+
+            fn __indices(&self) -> Option<Vec<usize>> {
+                let len = self.__len()?;
+                $(
+                    if self.$idx.__len() == Some(len) {
+                        return self.$idx.__indices();
+                    }
+                )*
+                unreachable!()
+            }
+
+            fn __contains(&self, index: usize) -> bool {
+                $(self.$idx.__contains(index))&&*
+            }
+
+            unsafe fn __get(&mut self, index: usize) -> Option<Self::Item> {
+                Some(($(self.$idx.__get(index)?,)*))
+            }
+
+            fn __track(&mut self, index: usize) {
+                $(self.$idx.__track(index);)*
+            }
+        }
+    };
+}
+
+impl_join_tuple!(A 0);
+impl_join_tuple!(A 0, B 1);
+impl_join_tuple!(A 0, B 1, D 2);
+impl_join_tuple!(A 0, B 1, D 2, E 3);
+impl_join_tuple!(A 0, B 1, D 2, E 3, F 4);
+impl_join_tuple!(A 0, B 1, D 2, E 3, F 4, G 5);
+impl_join_tuple!(A 0, B 1, D 2, E 3, F 4, G 5, H 6);
+impl_join_tuple!(A 0, B 1, D 2, E 3, F 4, G 5, H 6, I 7);
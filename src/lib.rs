@@ -1,20 +1,44 @@
 //! TODO: Add documentation including describing how the derive macros work
 
 pub use crate::aspect::Aspect;
+pub use crate::commands::Commands;
 pub use crate::component::{ComponentList, ComponentManager};
 pub use crate::entity::{BuildData, EditData, EntityData, ModifyData};
 pub use crate::entity::{Entity, EntityIter, IndexedEntity};
+pub use crate::event::{EventChannel, ReaderId};
+pub use crate::join::Join;
+pub use crate::resources::Resources;
 pub use crate::services::ServiceManager;
+pub use crate::storage::ComponentStorage;
 pub use crate::system::{
-    EntitySystem, InteractSystem, IntervalSystem, LazySystem, Process, System, SystemManager,
+    EntitySystem, FixedSystem, InteractSystem, IntervalSystem, LazySystem, Process, System,
+    SystemManager, ToggleSystem,
 };
 pub use crate::world::{DataHelper, World};
 
-pub use conniecs_derive::{Aspect, ComponentManager, ServiceManager, System, SystemManager};
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub use serde as __serde;
+
+pub use conniecs_derive::{
+    Aspect, Bundle, ComponentManager, ServiceManager, System, SystemManager,
+};
 
 pub mod aspect;
+pub mod clock;
+pub mod commands;
 pub mod component;
 pub mod entity;
+pub mod event;
+pub mod join;
+#[cfg(feature = "rayon")]
+pub mod par_iter;
+pub mod resources;
+#[cfg(feature = "serde")]
+pub mod serialize;
 pub mod services;
+pub mod snapshot;
+pub mod storage;
 pub mod system;
+pub mod tracking;
 pub mod world;
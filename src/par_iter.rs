@@ -0,0 +1,575 @@
+//! Parallel iteration with rayon. Only available with the `rayon` feature.
+//!
+//! Entity iterators and watchers can be turned into parallel iterators over
+//! their entities, and component lists can be iterated in parallel directly.
+//! Mutable access stays safe by only handing out one `&mut` per component:
+//! `par_iter_mut` covers a whole list, `par_with_entities_mut` the components
+//! of the entities a system is watching, and `par_join` works like `join`
+//! across several lists.
+//!
+//! ```
+//! # #[macro_use] extern crate conniecs_derive; extern crate conniecs;
+//! use conniecs::par_iter::ParJoin;
+//! use rayon::prelude::*;
+//! # #[derive(ComponentManager)] struct Components {
+//! # #[hot] pub position: conniecs::ComponentList<Components, f32>,
+//! # #[hot] pub velocity: conniecs::ComponentList<Components, f32>, }
+//! # #[derive(ServiceManager, Default)] struct Services {}
+//! # type DataHelper = conniecs::DataHelper<Components, Services>;
+//! # type EntityIter<'a> = conniecs::EntityIter<'a, Components>;
+//!
+//! fn integrate(entities: EntityIter, data: &mut DataHelper) {
+//!     let c = &mut data.components;
+//!     (&mut c.position, &c.velocity)
+//!         .par_join()
+//!         .for_each(|(pos, vel)| *pos += vel);
+//!
+//!     c.velocity
+//!         .par_with_entities_mut(entities)
+//!         .for_each(|(_, vel)| *vel *= 0.9);
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! Hot, dense and flag lists are split up between threads by entity index or
+//! by position in their storage, and entity iterators by ranges of entity
+//! index, so the work is shared out without a pass over everything first.
+//! Cold lists and custom storages have nothing to split on, so their
+//! components are gathered up on the calling thread before being shared out.
+//! Mutable access to a tracked list records its changes on the calling
+//! thread too.
+
+use fnv::FnvHashMap;
+use free_ranges::Range;
+use rayon::iter::plumbing::UnindexedConsumer;
+use rayon::prelude::*;
+use vec_map::VecMap;
+
+use std::marker::PhantomData;
+
+use crate::component::{Component, ComponentList, ComponentManager};
+use crate::entity::{Entity, EntityData, EntityIter, IndexedEntity};
+use crate::join::{Join, Maybe};
+use crate::storage::{FlagStorage, Parts, PartsMut, Slot};
+use crate::system::watcher::Watcher;
+use crate::tracking::Change;
+
+/// A parallel iterator over entities, split up by ranges of entity index.
+pub struct ParEntityIter<'a, C>
+where
+    C: ComponentManager,
+{
+    inner: ParEntities<'a, C>,
+}
+
+enum ParEntities<'a, C>
+where
+    C: ComponentManager,
+{
+    /// Every entity in the world from an index onwards.
+    Indexed(usize, &'a VecMap<IndexedEntity<C>>),
+    /// The ranges of indices a watcher has left to visit.
+    Watched(Vec<Range>, &'a FnvHashMap<usize, IndexedEntity<C>>),
+}
+
+impl<'a, C> IntoParallelIterator for EntityIter<'a, C>
+where
+    C: ComponentManager + Sync,
+{
+    type Iter = ParEntityIter<'a, C>;
+    type Item = EntityData<'a, C>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        let inner = match self {
+            EntityIter::Indexed(mut iter) => {
+                // Indices come out in order, so what's left is every entity
+                // from the next one on
+                let start = iter.iter.next().unwrap_or_else(|| iter.values.capacity());
+                ParEntities::Indexed(start, iter.values)
+            }
+            EntityIter::Watched(iter) => {
+                let current = Some(iter.current_range).filter(|range| !range.empty());
+                let ranges = current.into_iter().chain(iter.indices.cloned()).collect();
+                ParEntities::Watched(ranges, iter.entities)
+            }
+        };
+        ParEntityIter { inner }
+    }
+}
+
+impl<'a, C> ParallelIterator for ParEntityIter<'a, C>
+where
+    C: ComponentManager + Sync,
+{
+    type Item = EntityData<'a, C>;
+
+    fn drive_unindexed<R>(self, consumer: R) -> R::Result
+    where
+        R: UnindexedConsumer<Self::Item>,
+    {
+        match self.inner {
+            ParEntities::Indexed(start, values) => (start..values.capacity().max(start))
+                .into_par_iter()
+                .filter_map(move |index| values.get(index).map(EntityData))
+                .drive_unindexed(consumer),
+            ParEntities::Watched(ranges, entities) => ranges
+                .into_par_iter()
+                .flat_map(|range| range.min..range.max + 1)
+                .map(move |index| EntityData(&entities[&index]))
+                .drive_unindexed(consumer),
+        }
+    }
+}
+
+impl<C> Watcher<C>
+where
+    C: ComponentManager + Sync,
+{
+    pub fn par_iter(&self) -> ParEntityIter<'_, C> {
+        self.iter().into_par_iter()
+    }
+}
+
+/// A parallel iterator over the components of a list. See
+/// `ComponentList::par_iter`.
+pub struct ParIter<'a, T>
+where
+    T: Component,
+{
+    inner: Split<'a, T>,
+}
+
+/// A parallel iterator over the components of a list, mutably. See
+/// `ComponentList::par_iter_mut`.
+pub struct ParIterMut<'a, T>
+where
+    T: Component,
+{
+    inner: SplitMut<'a, T>,
+}
+
+/// How a storage is split up between threads.
+enum Split<'a, T> {
+    Slots(&'a [Slot<T>]),
+    Dense(&'a [(Entity, T)]),
+    Flags(&'a VecMap<Entity>),
+    Collected(Vec<(Entity, &'a T)>),
+}
+
+enum SplitMut<'a, T> {
+    Slots(&'a mut [Slot<T>]),
+    Dense(&'a mut [(Entity, T)]),
+    Flags(&'a VecMap<Entity>),
+    Collected(Vec<(Entity, &'a mut T)>),
+}
+
+/// The entities of a flag storage, fetched by index.
+fn flagged(flags: &VecMap<Entity>) -> impl ParallelIterator<Item = Entity> + '_ {
+    (0..flags.capacity())
+        .into_par_iter()
+        .filter_map(move |index| flags.get(index).copied())
+}
+
+impl<'a, T> ParallelIterator for ParIter<'a, T>
+where
+    T: Component + Sync,
+{
+    type Item = (Entity, &'a T);
+
+    fn drive_unindexed<R>(self, consumer: R) -> R::Result
+    where
+        R: UnindexedConsumer<Self::Item>,
+    {
+        match self.inner {
+            Split::Slots(slots) => slots
+                .par_iter()
+                .filter_map(|slot| slot.as_ref().map(|(entity, c)| (*entity, c)))
+                .drive_unindexed(consumer),
+            Split::Dense(dense) => dense
+                .par_iter()
+                .map(|(entity, c)| (*entity, c))
+                .drive_unindexed(consumer),
+            Split::Flags(flags) => flagged(flags)
+                .map(|entity| (entity, &*FlagStorage::<T>::value()))
+                .drive_unindexed(consumer),
+            Split::Collected(items) => items.into_par_iter().drive_unindexed(consumer),
+        }
+    }
+}
+
+impl<'a, T> ParallelIterator for ParIterMut<'a, T>
+where
+    T: Component + Send,
+{
+    type Item = (Entity, &'a mut T);
+
+    fn drive_unindexed<R>(self, consumer: R) -> R::Result
+    where
+        R: UnindexedConsumer<Self::Item>,
+    {
+        match self.inner {
+            SplitMut::Slots(slots) => slots
+                .par_iter_mut()
+                .filter_map(|slot| slot.as_mut().map(|(entity, c)| (*entity, c)))
+                .drive_unindexed(consumer),
+            SplitMut::Dense(dense) => dense
+                .par_iter_mut()
+                .map(|(entity, c)| (*entity, c))
+                .drive_unindexed(consumer),
+            SplitMut::Flags(flags) => flagged(flags)
+                .map(|entity| (entity, FlagStorage::<T>::value()))
+                .drive_unindexed(consumer),
+            SplitMut::Collected(items) => items.into_par_iter().drive_unindexed(consumer),
+        }
+    }
+}
+
+/// Hands out the components of a list mutably by index, to several threads
+/// at once.
+#[doc(hidden)]
+pub struct Shared<'a, T>
+where
+    T: Component,
+{
+    inner: SharedParts<'a, T>,
+    _marker: PhantomData<&'a mut T>,
+}
+
+enum SharedParts<'a, T> {
+    Slots(*mut Slot<T>, usize),
+    Dense(&'a VecMap<usize>, *mut (Entity, T)),
+    Flags(&'a VecMap<Entity>),
+    Other(FnvHashMap<usize, *mut T>),
+}
+
+// Safety: every component is only handed out once, see `Shared::get`
+unsafe impl<T> Send for Shared<'_, T> where T: Component + Send {}
+unsafe impl<T> Sync for Shared<'_, T> where T: Component + Send {}
+
+impl<'a, T> Shared<'a, T>
+where
+    T: Component,
+{
+    fn new(parts: PartsMut<'a, T>) -> Self {
+        let inner = match parts {
+            PartsMut::Slots(slots) => SharedParts::Slots(slots.as_mut_ptr(), slots.len()),
+            PartsMut::Dense(sparse, dense) => SharedParts::Dense(sparse, dense.as_mut_ptr()),
+            PartsMut::Flags(flags) => SharedParts::Flags(flags),
+            PartsMut::Other(iter) => SharedParts::Other(
+                iter.map(|(entity, c)| (entity.index(), c as *mut T))
+                    .collect(),
+            ),
+        };
+        Shared {
+            inner,
+            _marker: PhantomData,
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Must not be called more than once with the same index.
+    unsafe fn get(&self, index: usize) -> Option<&'a mut T> {
+        match &self.inner {
+            &SharedParts::Slots(slots, len) if index < len => {
+                (*slots.add(index)).as_mut().map(|(_, c)| c)
+            }
+            SharedParts::Slots(..) => None,
+            &SharedParts::Dense(sparse, dense) => {
+                let position = *sparse.get(index)?;
+                Some(&mut (*dense.add(position)).1)
+            }
+            SharedParts::Flags(flags) => flags.get(index).map(|_| FlagStorage::<T>::value()),
+            SharedParts::Other(components) => components.get(&index).map(|&c| &mut *c),
+        }
+    }
+}
+
+impl<C, T> ComponentList<C, T>
+where
+    C: ComponentManager,
+    T: Component,
+{
+    pub fn par_iter(&self) -> ParIter<'_, T>
+    where
+        T: Sync,
+    {
+        let inner = match self.inner.parts() {
+            Parts::Slots(slots) => Split::Slots(slots),
+            Parts::Dense(dense, _) => Split::Dense(dense),
+            Parts::Flags(flags) => Split::Flags(flags),
+            Parts::Other(iter) => Split::Collected(iter.collect()),
+        };
+        ParIter { inner }
+    }
+
+    /// On a tracked list, every component counts as modified.
+    pub fn par_iter_mut(&mut self) -> ParIterMut<'_, T>
+    where
+        T: Send,
+    {
+        self.track_all(Change::Modified);
+        let inner = match self.inner.parts_mut() {
+            PartsMut::Slots(slots) => SplitMut::Slots(slots),
+            PartsMut::Dense(_, dense) => SplitMut::Dense(dense),
+            PartsMut::Flags(flags) => SplitMut::Flags(flags),
+            PartsMut::Other(iter) => SplitMut::Collected(iter.collect()),
+        };
+        ParIterMut { inner }
+    }
+
+    /// Iterates over the components belonging to `entities`, skipping the
+    /// entities without one. On a tracked list, only those components count
+    /// as modified.
+    pub fn par_with_entities_mut<'e>(
+        &mut self,
+        entities: EntityIter<'e, C>,
+    ) -> impl ParallelIterator<Item = (EntityData<'e, C>, &mut T)>
+    where
+        C: Sync,
+        T: Send,
+    {
+        if self.is_tracked() {
+            let wanted: Vec<Entity> = Iterator::filter(entities.clone(), |&e| self.has(e))
+                .map(|e| **e)
+                .collect();
+            for entity in wanted {
+                self.track(entity, Change::Modified);
+            }
+        }
+
+        // Entities are unique, so each component is only fetched once
+        let components = Shared::new(self.inner.parts_mut());
+        entities.into_par_iter().filter_map(move |entity| {
+            let component = unsafe { components.get(entity.index()) };
+            component.map(|component| (entity, component))
+        })
+    }
+
+    /// The indices to look through for the components of this list.
+    fn par_indices(&self) -> __ParIndices {
+        match self.inner.parts() {
+            Parts::Slots(slots) => __ParIndices::Range(slots.len()),
+            Parts::Dense(_, bound) => __ParIndices::Range(bound),
+            Parts::Flags(flags) => __ParIndices::Range(flags.capacity()),
+            Parts::Other(_) => __ParIndices::Keys(self.inner.keys()),
+        }
+    }
+}
+
+/// The indices a parallel join looks through: either every index up to a
+/// bound, or the keys of a list which can't be split up.
+#[doc(hidden)]
+pub enum __ParIndices {
+    Range(usize),
+    Keys(Vec<usize>),
+}
+
+impl __ParIndices {
+    fn iter(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        match self {
+            __ParIndices::Range(end) => Box::new(0..*end),
+            __ParIndices::Keys(keys) => Box::new(keys.iter().copied()),
+        }
+    }
+}
+
+/// Parallel joins over component lists. See `Join`.
+pub trait ParJoin: Join {
+    /// The lists of the join, shared between threads.
+    #[doc(hidden)]
+    type __Shared: Send + Sync;
+
+    /// # Panics
+    ///
+    /// Panics if every list in the join is wrapped in `Maybe`, since there is
+    /// nothing to drive the iteration.
+    fn par_join(mut self) -> ParJoinIter<Self>
+    where
+        Self::Item: Send,
+    {
+        let indices = self
+            .__par_indices()
+            .expect("A join needs at least one required component list");
+        if self.__tracked() {
+            for index in indices.iter() {
+                if self.__contains(index) {
+                    self.__track(index);
+                }
+            }
+        }
+        ParJoinIter {
+            shared: self.__share(),
+            indices,
+        }
+    }
+
+    /// The indices of the smallest required list, if any.
+    #[doc(hidden)]
+    fn __par_indices(&self) -> Option<__ParIndices>;
+
+    /// Returns true if a tracked list is joined mutably.
+    #[doc(hidden)]
+    fn __tracked(&self) -> bool;
+
+    #[doc(hidden)]
+    fn __share(self) -> Self::__Shared;
+
+    /// Fetches the item for an index, if every required list has it.
+    ///
+    /// # Safety
+    ///
+    /// Must not be called more than once with the same index.
+    #[doc(hidden)]
+    unsafe fn __fetch(shared: &Self::__Shared, index: usize) -> Option<Self::Item>;
+}
+
+/// A parallel iterator over a join. See `ParJoin::par_join`.
+pub struct ParJoinIter<J>
+where
+    J: ParJoin,
+{
+    shared: J::__Shared,
+    indices: __ParIndices,
+}
+
+impl<J> ParallelIterator for ParJoinIter<J>
+where
+    J: ParJoin,
+    J::Item: Send,
+{
+    type Item = J::Item;
+
+    fn drive_unindexed<R>(self, consumer: R) -> R::Result
+    where
+        R: UnindexedConsumer<Self::Item>,
+    {
+        // Every index is only visited once
+        let shared = &self.shared;
+        let fetch = move |index| unsafe { J::__fetch(shared, index) };
+        match self.indices {
+            __ParIndices::Range(end) => (0..end)
+                .into_par_iter()
+                .filter_map(fetch)
+                .drive_unindexed(consumer),
+            __ParIndices::Keys(keys) => keys
+                .into_par_iter()
+                .filter_map(fetch)
+                .drive_unindexed(consumer),
+        }
+    }
+}
+
+impl<'a, C, T> ParJoin for &'a ComponentList<C, T>
+where
+    C: ComponentManager,
+    T: Component,
+    ComponentList<C, T>: Sync,
+{
+    type __Shared = Self;
+
+    fn __par_indices(&self) -> Option<__ParIndices> {
+        Some(self.par_indices())
+    }
+
+    fn __tracked(&self) -> bool {
+        false
+    }
+
+    fn __share(self) -> Self {
+        self
+    }
+
+    unsafe fn __fetch(shared: &Self, index: usize) -> Option<&'a T> {
+        shared.inner.get(index)
+    }
+}
+
+impl<'a, C, T> ParJoin for &'a mut ComponentList<C, T>
+where
+    C: ComponentManager,
+    T: Component + Send,
+{
+    type __Shared = Shared<'a, T>;
+
+    fn __par_indices(&self) -> Option<__ParIndices> {
+        Some(self.par_indices())
+    }
+
+    fn __tracked(&self) -> bool {
+        self.is_tracked()
+    }
+
+    fn __share(self) -> Shared<'a, T> {
+        Shared::new(self.inner.parts_mut())
+    }
+
+    unsafe fn __fetch(shared: &Shared<'a, T>, index: usize) -> Option<&'a mut T> {
+        shared.get(index)
+    }
+}
+
+impl<J> ParJoin for Maybe<J>
+where
+    J: ParJoin,
+{
+    type __Shared = J::__Shared;
+
+    fn __par_indices(&self) -> Option<__ParIndices> {
+        None
+    }
+
+    fn __tracked(&self) -> bool {
+        self.0.__tracked()
+    }
+
+    fn __share(self) -> J::__Shared {
+        self.0.__share()
+    }
+
+    unsafe fn __fetch(shared: &J::__Shared, index: usize) -> Option<Option<J::Item>> {
+        Some(J::__fetch(shared, index))
+    }
+}
+
+macro_rules! impl_par_join_tuple {
+    ($($name:ident $idx:tt),*) => {
+        impl<$($name),*> ParJoin for ($($name,)*)
+        where
+            $($name: ParJoin,)*
+        {
+            type __Shared = ($($name::__Shared,)*);
+
+            fn __par_indices(&self) -> Option<__ParIndices> {
+                let len = self.__len()?;
+                $(
+                    if self.$idx.__len() == Some(len) {
+                        return self.$idx.__par_indices();
+                    }
+                )*
+                unreachable!()
+            }
+
+            fn __tracked(&self) -> bool {
+                $(self.$idx.__tracked())||*
+            }
+
+            fn __share(self) -> Self::__Shared {
+                ($(self.$idx.__share(),)*)
+            }
+
+            unsafe fn __fetch(shared: &Self::__Shared, index: usize) -> Option<Self::Item> {
+                Some(($($name::__fetch(&shared.$idx, index)?,)*))
+            }
+        }
+    };
+}
+
+impl_par_join_tuple!(A 0);
+impl_par_join_tuple!(A 0, B 1);
+impl_par_join_tuple!(A 0, B 1, D 2);
+impl_par_join_tuple!(A 0, B 1, D 2, E 3);
+impl_par_join_tuple!(A 0, B 1, D 2, E 3, F 4);
+impl_par_join_tuple!(A 0, B 1, D 2, E 3, F 4, G 5);
+impl_par_join_tuple!(A 0, B 1, D 2, E 3, F 4, G 5, H 6);
+impl_par_join_tuple!(A 0, B 1, D 2, E 3, F 4, G 5, H 6, I 7);
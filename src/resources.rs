@@ -0,0 +1,121 @@
+//! Values stored by type, for code which can't add fields to the services.
+//!
+//! Every `DataHelper` has a `Resources` map next to its services. It holds at
+//! most one value of each type, so plugins usually keep their state in a type
+//! of their own.
+//!
+//! Systems which declare their access reach the map through
+//! `SplitData::resources` after naming `resources` in `#[reads]` or
+//! `#[writes]`. The map is borrowed as a whole, so two systems can only share
+//! it if neither writes to it.
+//!
+//! ```
+//! # #[macro_use] extern crate conniecs_derive; extern crate conniecs;
+//! # #[derive(ComponentManager)] struct Components {}
+//! # #[derive(ServiceManager, Default)] struct Services {}
+//! # #[derive(SystemManager)] struct Systems {}
+//! #[derive(Default)]
+//! struct Score(u32);
+//!
+//! # fn main() {
+//! let mut world = conniecs::World::<Systems>::new();
+//! world.data.resources.insert(Score(1));
+//! world.data.resources.get_or_default::<Score>().0 += 1;
+//! assert_eq!(world.data.resources.get::<Score>().unwrap().0, 2);
+//! # }
+//! ```
+
+use std::any::{Any, TypeId};
+use std::fmt;
+
+use fnv::FnvHashMap;
+
+#[derive(Default)]
+pub struct Resources {
+    values: FnvHashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl Resources {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Stores a value, returning the one of the same type it replaced.
+    pub fn insert<T>(&mut self, value: T) -> Option<T>
+    where
+        T: Any + Send + Sync,
+    {
+        let old = self.values.insert(TypeId::of::<T>(), Box::new(value))?;
+        Some(*old.downcast().unwrap())
+    }
+
+    pub fn remove<T>(&mut self) -> Option<T>
+    where
+        T: Any + Send + Sync,
+    {
+        let old = self.values.remove(&TypeId::of::<T>())?;
+        Some(*old.downcast().unwrap())
+    }
+
+    pub fn contains<T>(&self) -> bool
+    where
+        T: Any + Send + Sync,
+    {
+        self.values.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn get<T>(&self) -> Option<&T>
+    where
+        T: Any + Send + Sync,
+    {
+        self.values.get(&TypeId::of::<T>())?.downcast_ref()
+    }
+
+    pub fn get_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: Any + Send + Sync,
+    {
+        self.values.get_mut(&TypeId::of::<T>())?.downcast_mut()
+    }
+
+    /// Gets the value of type `T`, inserting one made by `make` if there
+    /// isn't one yet.
+    pub fn get_or_insert_with<T, F>(&mut self, make: F) -> &mut T
+    where
+        T: Any + Send + Sync,
+        F: FnOnce() -> T,
+    {
+        self.values
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(make()))
+            .downcast_mut()
+            .unwrap()
+    }
+
+    pub fn get_or_default<T>(&mut self) -> &mut T
+    where
+        T: Any + Send + Sync + Default,
+    {
+        self.get_or_insert_with(T::default)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+}
+
+impl fmt::Debug for Resources {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Resources")
+            .field("len", &self.values.len())
+            .finish()
+    }
+}
@@ -0,0 +1,131 @@
+//! Saving and loading whole worlds with serde. Only available with the
+//! `serde` feature.
+//!
+//! With the feature enabled, `#[derive(ComponentManager)]` on a struct marked
+//! `#[serialize]` implements `SerializeComponents`, which needs every
+//! component type to implement `Serialize` and `Deserialize`. Managers without
+//! the attribute are unaffected by the feature. Lists marked `#[transient]`
+//! are left out of saves and come back empty. Each component is stored
+//! alongside the entity it belongs to, so entity handles kept inside
+//! components or elsewhere in your save stay valid once it's loaded.
+//!
+//! `World::save` writes out the entities, components and tick, and
+//! `World::load` replaces the contents of a world with them before
+//! activating every loaded entity in the systems so their watchers rebuild.
+//! A save with a component belonging to an entity which isn't in the save is
+//! refused.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::component::{Component, ComponentList, ComponentManager};
+use crate::entity::{Entity, Generation};
+use crate::tracking::Tick;
+
+/// Implemented by `#[derive(ComponentManager)]` for structs marked
+/// `#[serialize]` when the `serde` feature is enabled.
+pub trait SerializeComponents: ComponentManager {
+    fn serialize_components<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer;
+
+    fn deserialize_components<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+
+    /// Finds a saved component whose entity `valid` rejects.
+    #[doc(hidden)]
+    fn __check_entities<F>(&self, valid: F) -> Result<(), Entity>
+    where
+        F: Fn(Entity) -> bool;
+}
+
+/// Lets the derive name the component type of a list.
+#[doc(hidden)]
+pub trait __ListComponent {
+    type Component;
+}
+
+impl<C, T> __ListComponent for ComponentList<C, T>
+where
+    C: ComponentManager,
+    T: Component,
+{
+    type Component = T;
+}
+
+/// Serializes as a sequence of `(Entity, T)` pairs.
+impl<C, T> Serialize for ComponentList<C, T>
+where
+    C: ComponentManager,
+    T: Component + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SavedEntities {
+    pub(crate) generations: Vec<Generation>,
+    pub(crate) entities: Vec<Entity>,
+    /// Each child with its parent, parents listed first.
+    #[serde(default)]
+    pub(crate) parents: Vec<(Entity, Entity)>,
+}
+
+struct SavedComponents<'a, C>(&'a C);
+
+impl<C> Serialize for SavedComponents<'_, C>
+where
+    C: SerializeComponents,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize_components(serializer)
+    }
+}
+
+pub(crate) struct LoadedComponents<C>(pub(crate) C);
+
+impl<'de, C> Deserialize<'de> for LoadedComponents<C>
+where
+    C: SerializeComponents,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        C::deserialize_components(deserializer).map(LoadedComponents)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(bound = "C: SerializeComponents")]
+pub(crate) struct SavedWorld<'a, C> {
+    tick: Tick,
+    entities: SavedEntities,
+    components: SavedComponents<'a, C>,
+}
+
+impl<'a, C> SavedWorld<'a, C> {
+    pub(crate) fn new(tick: Tick, entities: SavedEntities, components: &'a C) -> Self {
+        SavedWorld {
+            tick,
+            entities,
+            components: SavedComponents(components),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(bound = "C: SerializeComponents")]
+pub(crate) struct LoadedWorld<C> {
+    pub(crate) tick: Tick,
+    pub(crate) entities: SavedEntities,
+    pub(crate) components: LoadedComponents<C>,
+}
@@ -1,10 +1,82 @@
 //! TODO: Add documentation including describing how the derive macros work
 
+use std::any::{Any, TypeId};
+
 pub trait ServiceManager: 'static {
+    /// The first service of type `T`, if there is one.
+    fn get<T>(&self) -> Option<&T>
+    where
+        T: Any,
+    {
+        self.__service_by_type(TypeId::of::<T>())?.downcast_ref()
+    }
+
+    /// The first service of type `T`, if there is one.
+    fn get_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: Any,
+    {
+        self.__service_by_type_mut(TypeId::of::<T>())?
+            .downcast_mut()
+    }
+
+    #[doc(hidden)]
+    fn __service_by_type(&self, ty: TypeId) -> Option<&dyn Any> {
+        let _ = ty;
+        None
+    }
+
+    #[doc(hidden)]
+    fn __service_by_type_mut(&mut self, ty: TypeId) -> Option<&mut dyn Any> {
+        let _ = ty;
+        None
+    }
+
+    /// Points at the service in the field with the given name, without
+    /// creating a reference to the rest of the manager. `this` must be valid.
+    #[doc(hidden)]
+    unsafe fn __service_ptr(this: *mut Self, name: &str) -> Option<*mut dyn Any>
+    where
+        Self: Sized,
+    {
+        let _ = (this, name);
+        None
+    }
+
+    /// Swaps the buffers of every `#[events]` channel.
+    #[doc(hidden)]
+    fn __swap_events(&mut self) {}
+
+    /// Runs the `#[on_begin_frame]` hooks.
+    #[doc(hidden)]
+    fn __begin_frame(&mut self) {}
+
+    /// Runs the `#[on_end_frame]` hooks.
+    #[doc(hidden)]
+    fn __end_frame(&mut self) {}
+
+    /// Runs the `#[on_wipe]` hooks.
+    #[doc(hidden)]
+    fn __wipe(&mut self) {}
+
     #[doc(hidden)]
     fn __please_use_the_derive_attribute();
 }
 
+/// Called on services marked with `#[on_begin_frame]`, `#[on_end_frame]` or
+/// `#[on_wipe]` when the attribute doesn't name a function of its own.
+///
+/// `World::update` runs the begin frame hooks before anything else is
+/// flushed or processed, and the end frame hooks after the last flush.
+/// `World::wipe` runs the wipe hooks after every entity is gone, which also
+/// happens when a saved world is loaded. Hooks of the same kind run in the
+/// order the fields are declared.
+pub trait ServiceHooks {
+    fn begin_frame(&mut self) {}
+    fn end_frame(&mut self) {}
+    fn wipe(&mut self) {}
+}
+
 impl ServiceManager for () {
     #[doc(hidden)]
     fn __please_use_the_derive_attribute() {}
@@ -0,0 +1,50 @@
+//! Capturing and rewinding the state of a world, for rollback networking.
+//!
+//! `World::snapshot` copies the entities and every component, which needs
+//! your `ComponentManager` to implement `Clone`. Deriving it works as long as
+//! every component type is `Clone`:
+//!
+//! ```
+//! # #[macro_use] extern crate conniecs_derive; extern crate conniecs;
+//! #[derive(Clone, ComponentManager)]
+//! pub struct Components {
+//!     #[hot]
+//!     pub position: conniecs::ComponentList<Components, (f32, f32)>,
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! `World::restore` deactivates every entity in the systems, puts the
+//! snapshot's state in place and then activates every restored entity, so
+//! watchers end up interested in exactly the entities that match. Services and
+//! system state aren't part of a snapshot.
+//!
+//! Restoring rewinds entity allocation as well, so that replaying the same
+//! inputs creates the same entities. Handles to entities created after the
+//! snapshot aren't safe to keep across a restore, since they can come to
+//! refer to whatever is created in their place.
+
+use crate::component::ComponentManager;
+use crate::entity::EntityManager;
+use crate::tracking::Tick;
+
+/// An owned copy of the entities and components of a world at some tick.
+pub struct Snapshot<C>
+where
+    C: ComponentManager,
+{
+    pub(crate) tick: Tick,
+    pub(crate) entities: EntityManager<C>,
+    pub(crate) components: C,
+}
+
+impl<C> Snapshot<C>
+where
+    C: ComponentManager,
+{
+    /// The tick the world was at when the snapshot was taken.
+    #[inline]
+    pub fn tick(&self) -> Tick {
+        self.tick
+    }
+}
@@ -0,0 +1,782 @@
+//! The containers backing component lists.
+//!
+//! Every list picks its storage with an attribute on its field in the
+//! `ComponentManager` struct:
+//!
+//! - `#[hot]` or `#[storage(hot)]` uses a `VecMapStorage`, a vector indexed
+//!   by entity. It is the fastest to look up, at the cost of a slot for every
+//!   entity whether or not it has the component.
+//! - `#[storage(cold)]` uses a `HashMapStorage`, for components few entities
+//!   have.
+//! - `#[storage(dense)]` uses a `DenseVecStorage`, which keeps components
+//!   packed together for fast iteration with a sparse index for lookups.
+//! - `#[storage(flag)]` uses a `FlagStorage`, which only remembers which
+//!   entities have the component. It's only for zero-sized tag components,
+//!   and using it for anything else fails to compile.
+//! - `#[storage("path::to::MyStorage")]` uses your own type implementing
+//!   `ComponentStorage` and `Default`.
+//!
+//! The builtin storages can also be named after their types, as in
+//! `#[storage(DenseVec)]`.
+
+use fnv::FnvHashMap;
+use vec_map::VecMap;
+
+use std::collections::{hash_map, HashMap};
+use std::fmt;
+use std::iter::Flatten;
+use std::marker::PhantomData;
+use std::{mem, ptr, slice};
+
+use crate::component::Component;
+use crate::entity::Entity;
+
+/// A container of components keyed by entity index. Implement this to back a
+/// component list with your own data structure.
+pub trait ComponentStorage<T>: 'static
+where
+    T: Component,
+{
+    /// Stores the component for the entity, returning the one it replaced.
+    fn insert(&mut self, entity: Entity, component: T) -> Option<T>;
+
+    fn remove(&mut self, index: usize) -> Option<T>;
+
+    fn get(&self, index: usize) -> Option<&T>;
+
+    fn get_mut(&mut self, index: usize) -> Option<&mut T>;
+
+    fn contains(&self, index: usize) -> bool {
+        self.get(index).is_some()
+    }
+
+    /// The entity whose component is stored at the index. The default
+    /// implementation searches through `iter`.
+    fn entity(&self, index: usize) -> Option<Entity> {
+        self.iter()
+            .map(|(entity, _)| entity)
+            .find(|entity| entity.index() == index)
+    }
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of components which can be stored without reallocating.
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+
+    fn clear(&mut self);
+
+    /// Iterates over every component along with the entity it belongs to.
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (Entity, &'a T)> + 'a>;
+
+    fn iter_mut<'a>(&'a mut self) -> Box<dyn Iterator<Item = (Entity, &'a mut T)> + 'a>;
+
+    /// Removes and returns every component.
+    fn drain(&mut self) -> Vec<(Entity, T)>;
+
+    /// Copies the storage, which is needed to clone the list it backs. The
+    /// default implementation returns `None`, which makes cloning the list
+    /// panic.
+    fn try_clone(&self) -> Option<BoxedStorage<T>> {
+        None
+    }
+}
+
+/// A custom storage backing a component list. Storages have to be `Send` and
+/// `Sync` so that worlds can be moved and shared between threads.
+pub type BoxedStorage<T> = Box<dyn ComponentStorage<T> + Send + Sync>;
+
+/// A slot of a hot storage, holding the component of the entity at its index.
+pub(crate) type Slot<T> = Option<(Entity, T)>;
+
+/// Components stored in a vector indexed by entity. Selected with `#[hot]`.
+#[derive(Clone)]
+pub struct VecMapStorage<T> {
+    slots: Vec<Slot<T>>,
+    len: usize,
+}
+
+impl<T> Default for VecMapStorage<T> {
+    fn default() -> Self {
+        VecMapStorage {
+            slots: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<T> ComponentStorage<T> for VecMapStorage<T>
+where
+    T: Component,
+{
+    fn insert(&mut self, entity: Entity, component: T) -> Option<T> {
+        let index = entity.index();
+        if index >= self.slots.len() {
+            self.slots.resize_with(index + 1, || None);
+        }
+        let old = self.slots[index].replace((entity, component));
+        if old.is_none() {
+            self.len += 1;
+        }
+        old.map(|(_, component)| component)
+    }
+
+    fn remove(&mut self, index: usize) -> Option<T> {
+        let old = self.slots.get_mut(index)?.take();
+        if old.is_some() {
+            self.len -= 1;
+        }
+        old.map(|(_, component)| component)
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        match self.slots.get(index) {
+            Some(Some((_, component))) => Some(component),
+            _ => None,
+        }
+    }
+
+    fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        match self.slots.get_mut(index) {
+            Some(Some((_, component))) => Some(component),
+            _ => None,
+        }
+    }
+
+    fn entity(&self, index: usize) -> Option<Entity> {
+        match self.slots.get(index) {
+            Some(&Some((entity, _))) => Some(entity),
+            _ => None,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    fn clear(&mut self) {
+        self.slots.clear();
+        self.len = 0;
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (Entity, &'a T)> + 'a> {
+        Box::new(Iter::Hot(self.slots.iter().flatten()))
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> Box<dyn Iterator<Item = (Entity, &'a mut T)> + 'a> {
+        Box::new(IterMut::Hot(self.slots.iter_mut().flatten()))
+    }
+
+    fn drain(&mut self) -> Vec<(Entity, T)> {
+        self.len = 0;
+        self.slots.drain(..).flatten().collect()
+    }
+}
+
+/// Components stored in a hash map keyed by entity. Selected with
+/// `#[storage(cold)]`.
+#[derive(Clone)]
+pub struct HashMapStorage<T> {
+    map: FnvHashMap<usize, (Entity, T)>,
+}
+
+impl<T> Default for HashMapStorage<T> {
+    fn default() -> Self {
+        HashMapStorage {
+            map: HashMap::with_hasher(Default::default()),
+        }
+    }
+}
+
+impl<T> ComponentStorage<T> for HashMapStorage<T>
+where
+    T: Component,
+{
+    fn insert(&mut self, entity: Entity, component: T) -> Option<T> {
+        let old = self.map.insert(entity.index(), (entity, component));
+        old.map(|(_, component)| component)
+    }
+
+    fn remove(&mut self, index: usize) -> Option<T> {
+        self.map.remove(&index).map(|(_, component)| component)
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        self.map.get(&index).map(|(_, component)| component)
+    }
+
+    fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.map.get_mut(&index).map(|(_, component)| component)
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        self.map.contains_key(&index)
+    }
+
+    fn entity(&self, index: usize) -> Option<Entity> {
+        self.map.get(&index).map(|&(entity, _)| entity)
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (Entity, &'a T)> + 'a> {
+        Box::new(Iter::Cold(self.map.values()))
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> Box<dyn Iterator<Item = (Entity, &'a mut T)> + 'a> {
+        Box::new(IterMut::Cold(self.map.values_mut()))
+    }
+
+    fn drain(&mut self) -> Vec<(Entity, T)> {
+        self.map.drain().map(|(_, entry)| entry).collect()
+    }
+}
+
+/// Components packed into a vector, with a sparse index from entity to
+/// position. Removal swaps the last component into the hole, so the order
+/// of iteration changes as components are removed. Selected with
+/// `#[storage(dense)]`.
+#[derive(Clone)]
+pub struct DenseVecStorage<T> {
+    sparse: VecMap<usize>,
+    dense: Vec<(Entity, T)>,
+}
+
+impl<T> Default for DenseVecStorage<T> {
+    fn default() -> Self {
+        DenseVecStorage {
+            sparse: VecMap::new(),
+            dense: Vec::new(),
+        }
+    }
+}
+
+impl<T> ComponentStorage<T> for DenseVecStorage<T>
+where
+    T: Component,
+{
+    fn insert(&mut self, entity: Entity, component: T) -> Option<T> {
+        match self.sparse.get(entity.index()) {
+            Some(&position) => {
+                let old = mem::replace(&mut self.dense[position], (entity, component));
+                Some(old.1)
+            }
+            None => {
+                self.sparse.insert(entity.index(), self.dense.len());
+                self.dense.push((entity, component));
+                None
+            }
+        }
+    }
+
+    fn remove(&mut self, index: usize) -> Option<T> {
+        let position = self.sparse.remove(index)?;
+        let (_, component) = self.dense.swap_remove(position);
+        if let Some(&(moved, _)) = self.dense.get(position) {
+            self.sparse.insert(moved.index(), position);
+        }
+        Some(component)
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        let position = *self.sparse.get(index)?;
+        Some(&self.dense[position].1)
+    }
+
+    fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let position = *self.sparse.get(index)?;
+        Some(&mut self.dense[position].1)
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        self.sparse.contains_key(index)
+    }
+
+    fn entity(&self, index: usize) -> Option<Entity> {
+        let position = *self.sparse.get(index)?;
+        Some(self.dense[position].0)
+    }
+
+    fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.dense.capacity()
+    }
+
+    fn clear(&mut self) {
+        self.sparse.clear();
+        self.dense.clear();
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (Entity, &'a T)> + 'a> {
+        Box::new(Iter::Dense(self.dense.iter()))
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> Box<dyn Iterator<Item = (Entity, &'a mut T)> + 'a> {
+        Box::new(IterMut::Dense(self.dense.iter_mut()))
+    }
+
+    fn drain(&mut self) -> Vec<(Entity, T)> {
+        self.sparse.clear();
+        self.dense.drain(..).collect()
+    }
+}
+
+/// Only records which entities have the component, for zero-sized tags.
+/// Selected with `#[storage(flag)]`.
+///
+/// Creating one for a `T` which isn't zero-sized fails to compile:
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate conniecs_derive; extern crate conniecs;
+/// #[derive(ComponentManager)]
+/// struct Components {
+///     #[storage(flag)]
+///     pub health: conniecs::ComponentList<Components, u32>,
+/// }
+/// # #[derive(ServiceManager, Default)] struct Services {}
+/// # #[derive(SystemManager)] struct Systems {}
+/// # fn main() { conniecs::World::<Systems>::new(); }
+/// ```
+pub struct FlagStorage<T> {
+    entities: VecMap<Entity>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Default for FlagStorage<T> {
+    fn default() -> Self {
+        let () = Self::ZERO_SIZED;
+        FlagStorage {
+            entities: VecMap::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> FlagStorage<T> {
+    /// Evaluated wherever a flag storage is created, so that using one for a
+    /// component with a size is caught while compiling.
+    const ZERO_SIZED: () = assert!(
+        mem::size_of::<T>() == 0,
+        "Flag storage can only be used for zero-sized components"
+    );
+
+    #[inline]
+    pub(crate) fn value<'a>() -> &'a mut T {
+        // T is zero-sized, so any aligned non-null pointer refers to a valid
+        // value and references to it can't alias anything.
+        unsafe { &mut *ptr::NonNull::dangling().as_ptr() }
+    }
+
+    #[inline]
+    fn take() -> T {
+        unsafe { ptr::read(ptr::NonNull::dangling().as_ptr()) }
+    }
+}
+
+impl<T> Clone for FlagStorage<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        for _ in self.entities.values() {
+            mem::forget(Self::value().clone());
+        }
+        FlagStorage {
+            entities: self.entities.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Drop for FlagStorage<T> {
+    fn drop(&mut self) {
+        if mem::needs_drop::<T>() {
+            for _ in self.entities.values() {
+                drop(Self::take());
+            }
+        }
+    }
+}
+
+impl<T> ComponentStorage<T> for FlagStorage<T>
+where
+    T: Component,
+{
+    fn insert(&mut self, entity: Entity, component: T) -> Option<T> {
+        // The value itself lives nowhere; it is conjured back up on removal
+        mem::forget(component);
+        self.entities
+            .insert(entity.index(), entity)
+            .map(|_| Self::take())
+    }
+
+    fn remove(&mut self, index: usize) -> Option<T> {
+        self.entities.remove(index).map(|_| Self::take())
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        if self.entities.contains_key(index) {
+            Some(Self::value())
+        } else {
+            None
+        }
+    }
+
+    fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if self.entities.contains_key(index) {
+            Some(Self::value())
+        } else {
+            None
+        }
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        self.entities.contains_key(index)
+    }
+
+    fn entity(&self, index: usize) -> Option<Entity> {
+        self.entities.get(index).cloned()
+    }
+
+    fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.entities.capacity()
+    }
+
+    fn clear(&mut self) {
+        for _ in self.drain() {}
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (Entity, &'a T)> + 'a> {
+        Box::new(Iter::Flag(self.entities.values(), PhantomData))
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> Box<dyn Iterator<Item = (Entity, &'a mut T)> + 'a> {
+        Box::new(IterMut::Flag(self.entities.values(), PhantomData))
+    }
+
+    fn drain(&mut self) -> Vec<(Entity, T)> {
+        self.entities
+            .drain()
+            .map(|(_, entity)| (entity, Self::take()))
+            .collect()
+    }
+}
+
+/// The storage of a component list. The builtin storages are matched on
+/// directly so that lookups don't go through a vtable.
+pub(crate) enum InnerComponentList<T>
+where
+    T: Component,
+{
+    Hot(VecMapStorage<T>),
+    Cold(HashMapStorage<T>),
+    Dense(DenseVecStorage<T>),
+    Flag(FlagStorage<T>),
+    Custom(BoxedStorage<T>),
+}
+
+macro_rules! dispatch {
+    ($inner:expr, $storage:ident => $body:expr) => {
+        match $inner {
+            InnerComponentList::Hot($storage) => $body,
+            InnerComponentList::Cold($storage) => $body,
+            InnerComponentList::Dense($storage) => $body,
+            InnerComponentList::Flag($storage) => $body,
+            InnerComponentList::Custom($storage) => $body,
+        }
+    };
+}
+
+impl<T> InnerComponentList<T>
+where
+    T: Component,
+{
+    pub(crate) fn insert(&mut self, entity: Entity, component: T) -> Option<T> {
+        dispatch!(self, storage => storage.insert(entity, component))
+    }
+
+    pub(crate) fn remove(&mut self, index: usize) -> Option<T> {
+        dispatch!(self, storage => storage.remove(index))
+    }
+
+    pub(crate) fn contains_key(&self, index: usize) -> bool {
+        dispatch!(self, storage => storage.contains(index))
+    }
+
+    pub(crate) fn entity(&self, index: usize) -> Option<Entity> {
+        dispatch!(self, storage => storage.entity(index))
+    }
+
+    pub(crate) fn index(&self, index: usize) -> &T {
+        self.get(index).unwrap_or_else(|| {
+            panic!(
+                "Entity at index {} does not have this component attached",
+                index
+            )
+        })
+    }
+
+    pub(crate) fn index_mut(&mut self, index: usize) -> &mut T {
+        self.get_mut(index).unwrap_or_else(|| {
+            panic!(
+                "Entity at index {} does not have this component attached",
+                index
+            )
+        })
+    }
+
+    pub(crate) fn get(&self, index: usize) -> Option<&T> {
+        dispatch!(self, storage => storage.get(index))
+    }
+
+    pub(crate) fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        dispatch!(self, storage => storage.get_mut(index))
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        dispatch!(self, storage => storage.len())
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        dispatch!(self, storage => storage.capacity())
+    }
+
+    pub(crate) fn keys(&self) -> Vec<usize> {
+        self.iter().map(|(entity, _)| entity.index()).collect()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        dispatch!(self, storage => storage.clear())
+    }
+
+    pub(crate) fn drain(&mut self) -> Vec<(Entity, T)> {
+        dispatch!(self, storage => storage.drain())
+    }
+
+    pub(crate) fn iter(&self) -> Iter<'_, T> {
+        match self {
+            InnerComponentList::Hot(storage) => Iter::Hot(storage.slots.iter().flatten()),
+            InnerComponentList::Cold(storage) => Iter::Cold(storage.map.values()),
+            InnerComponentList::Dense(storage) => Iter::Dense(storage.dense.iter()),
+            InnerComponentList::Flag(storage) => Iter::Flag(storage.entities.values(), PhantomData),
+            InnerComponentList::Custom(storage) => Iter::Custom(storage.iter()),
+        }
+    }
+
+    pub(crate) fn iter_mut(&mut self) -> IterMut<'_, T> {
+        match self {
+            InnerComponentList::Hot(storage) => IterMut::Hot(storage.slots.iter_mut().flatten()),
+            InnerComponentList::Cold(storage) => IterMut::Cold(storage.map.values_mut()),
+            InnerComponentList::Dense(storage) => IterMut::Dense(storage.dense.iter_mut()),
+            InnerComponentList::Flag(storage) => {
+                IterMut::Flag(storage.entities.values(), PhantomData)
+            }
+            InnerComponentList::Custom(storage) => IterMut::Custom(storage.iter_mut()),
+        }
+    }
+}
+
+/// The contents of a storage laid out so that they can be split up between
+/// threads. Storages without a layout to split fall back on their iterators.
+#[cfg(feature = "rayon")]
+pub(crate) enum Parts<'a, T>
+where
+    T: Component,
+{
+    Slots(&'a [Slot<T>]),
+    /// The packed components, and one past the highest index among them
+    Dense(&'a [(Entity, T)], usize),
+    Flags(&'a VecMap<Entity>),
+    Other(Iter<'a, T>),
+}
+
+#[cfg(feature = "rayon")]
+pub(crate) enum PartsMut<'a, T>
+where
+    T: Component,
+{
+    Slots(&'a mut [Slot<T>]),
+    Dense(&'a VecMap<usize>, &'a mut [(Entity, T)]),
+    Flags(&'a VecMap<Entity>),
+    Other(IterMut<'a, T>),
+}
+
+#[cfg(feature = "rayon")]
+impl<T> InnerComponentList<T>
+where
+    T: Component,
+{
+    pub(crate) fn parts(&self) -> Parts<'_, T> {
+        match self {
+            InnerComponentList::Hot(storage) => Parts::Slots(&storage.slots),
+            InnerComponentList::Dense(storage) => {
+                Parts::Dense(&storage.dense, storage.sparse.capacity())
+            }
+            InnerComponentList::Flag(storage) => Parts::Flags(&storage.entities),
+            _ => Parts::Other(self.iter()),
+        }
+    }
+
+    pub(crate) fn parts_mut(&mut self) -> PartsMut<'_, T> {
+        match self {
+            InnerComponentList::Hot(storage) => PartsMut::Slots(&mut storage.slots),
+            InnerComponentList::Dense(storage) => {
+                PartsMut::Dense(&storage.sparse, &mut storage.dense)
+            }
+            InnerComponentList::Flag(storage) => PartsMut::Flags(&storage.entities),
+            _ => PartsMut::Other(self.iter_mut()),
+        }
+    }
+}
+
+impl<T> Clone for InnerComponentList<T>
+where
+    T: Component + Clone,
+{
+    fn clone(&self) -> Self {
+        match self {
+            InnerComponentList::Hot(storage) => InnerComponentList::Hot(storage.clone()),
+            InnerComponentList::Cold(storage) => InnerComponentList::Cold(storage.clone()),
+            InnerComponentList::Dense(storage) => InnerComponentList::Dense(storage.clone()),
+            InnerComponentList::Flag(storage) => InnerComponentList::Flag(storage.clone()),
+            InnerComponentList::Custom(storage) => InnerComponentList::Custom(
+                storage
+                    .try_clone()
+                    .expect("This list's custom storage doesn't support cloning"),
+            ),
+        }
+    }
+}
+
+impl<T> fmt::Debug for InnerComponentList<T>
+where
+    T: Component + fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Iterates over the components of a list along with their entities.
+pub enum Iter<'a, T>
+where
+    T: Component,
+{
+    #[doc(hidden)]
+    Hot(Flatten<slice::Iter<'a, Slot<T>>>),
+    #[doc(hidden)]
+    Cold(hash_map::Values<'a, usize, (Entity, T)>),
+    #[doc(hidden)]
+    Dense(slice::Iter<'a, (Entity, T)>),
+    #[doc(hidden)]
+    Flag(vec_map::Values<'a, Entity>, PhantomData<&'a T>),
+    #[doc(hidden)]
+    Custom(Box<dyn Iterator<Item = (Entity, &'a T)> + 'a>),
+}
+
+impl<'a, T> Iterator for Iter<'a, T>
+where
+    T: Component,
+{
+    type Item = (Entity, &'a T);
+
+    fn next(&mut self) -> Option<(Entity, &'a T)> {
+        let entry = match self {
+            Iter::Hot(iter) => iter.next(),
+            Iter::Cold(iter) => iter.next(),
+            Iter::Dense(iter) => iter.next(),
+            Iter::Flag(iter, _) => {
+                return iter
+                    .next()
+                    .map(|&entity| (entity, &*FlagStorage::<T>::value()))
+            }
+            Iter::Custom(iter) => return iter.next(),
+        };
+        entry.map(|(entity, component)| (*entity, component))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Iter::Hot(iter) => iter.size_hint(),
+            Iter::Cold(iter) => iter.size_hint(),
+            Iter::Dense(iter) => iter.size_hint(),
+            Iter::Flag(iter, _) => iter.size_hint(),
+            Iter::Custom(iter) => iter.size_hint(),
+        }
+    }
+}
+
+/// Iterates over the components of a list mutably along with their entities.
+pub enum IterMut<'a, T>
+where
+    T: Component,
+{
+    #[doc(hidden)]
+    Hot(Flatten<slice::IterMut<'a, Slot<T>>>),
+    #[doc(hidden)]
+    Cold(hash_map::ValuesMut<'a, usize, (Entity, T)>),
+    #[doc(hidden)]
+    Dense(slice::IterMut<'a, (Entity, T)>),
+    #[doc(hidden)]
+    Flag(vec_map::Values<'a, Entity>, PhantomData<&'a mut T>),
+    #[doc(hidden)]
+    Custom(Box<dyn Iterator<Item = (Entity, &'a mut T)> + 'a>),
+}
+
+impl<'a, T> Iterator for IterMut<'a, T>
+where
+    T: Component,
+{
+    type Item = (Entity, &'a mut T);
+
+    fn next(&mut self) -> Option<(Entity, &'a mut T)> {
+        let entry = match self {
+            IterMut::Hot(iter) => iter.next(),
+            IterMut::Cold(iter) => iter.next(),
+            IterMut::Dense(iter) => iter.next(),
+            IterMut::Flag(iter, _) => {
+                return iter
+                    .next()
+                    .map(|&entity| (entity, FlagStorage::<T>::value()))
+            }
+            IterMut::Custom(iter) => return iter.next(),
+        };
+        entry.map(|(entity, component)| (*entity, component))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            IterMut::Hot(iter) => iter.size_hint(),
+            IterMut::Cold(iter) => iter.size_hint(),
+            IterMut::Dense(iter) => iter.size_hint(),
+            IterMut::Flag(iter, _) => iter.size_hint(),
+            IterMut::Custom(iter) => iter.size_hint(),
+        }
+    }
+}
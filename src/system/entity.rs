@@ -3,9 +3,11 @@
 use std::ops::{Deref, DerefMut};
 
 use crate::aspect::Aspect;
+use crate::component::ComponentManager;
 use crate::entity::{EntityData, EntityIter};
+use crate::services::ServiceManager;
 use crate::system::watcher::Watcher;
-use crate::system::{Process, System};
+use crate::system::{Access, Process, Split, SplitData, SplitProcess, System};
 use crate::world::DataHelper;
 
 pub trait EntityProcess: FilteredEntitySystem {
@@ -14,8 +16,25 @@ pub trait EntityProcess: FilteredEntitySystem {
         entities: EntityIter<'a, Self::Components>,
         data: &mut DataHelper<Self::Components, Self::Services>,
     );
+
+    /// See `Process::split`.
+    fn split(&mut self) -> Option<SplitEntity<'_, Self::Components, Self::Services>> {
+        None
+    }
+}
+
+/// See `SplitProcess`.
+pub trait SplitEntityProcess<C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    fn process_split<'a>(&mut self, entities: EntityIter<'a, C>, data: &mut SplitData<C, M>);
 }
 
+/// What `EntityProcess::split` returns.
+pub type SplitEntity<'a, C, M> = (Access, &'a mut dyn SplitEntityProcess<C, M>);
+
 /// This trait is implemented automatically when you `#[derive(System)]` with the following:
 ///
 /// ```
@@ -106,6 +125,15 @@ where
     }
 }
 
+impl<T> Default for EntitySystem<T>
+where
+    T: EntityProcess,
+{
+    fn default() -> Self {
+        EntitySystem::new()
+    }
+}
+
 impl<T> System for EntitySystem<T>
 where
     T: EntityProcess,
@@ -155,4 +183,20 @@ where
     fn process(&mut self, data: &mut DataHelper<T::Components, T::Services>) {
         self.inner.process(self.watcher.iter(), data);
     }
+
+    fn split(&mut self) -> Option<Split<'_, T::Components, T::Services>> {
+        let (access, _) = self.inner.split()?;
+        Some((access, self))
+    }
+}
+
+impl<T> SplitProcess<T::Components, T::Services> for EntitySystem<T>
+where
+    T: EntityProcess,
+{
+    fn process_split(&mut self, data: &mut SplitData<T::Components, T::Services>) {
+        if let Some((_, inner)) = self.inner.split() {
+            inner.process_split(self.watcher.iter(), data);
+        }
+    }
 }
@@ -0,0 +1,186 @@
+//! Systems which run at a fixed rate, independent of how often the world is
+//! updated.
+//!
+//! `#[system_type(fixed)]` systems collect the time passed between updates,
+//! as measured by the world's `Clock`, and run their process function once
+//! for every whole timestep that has built up, passing the length of the step
+//! in seconds. When updates fall too far behind, at most `max_steps` steps
+//! are run in one update and the rest of the backlog is dropped, so a slow
+//! frame can't snowball.
+//!
+//! ```
+//! # #[macro_use] extern crate conniecs_derive; extern crate conniecs;
+//! # #[derive(ComponentManager)] struct Components;
+//! # #[derive(ServiceManager, Default)] struct Services {}
+//! # type DataHelper = conniecs::DataHelper<Components, Services>;
+//! #[derive(Default, System)]
+//! #[system_type(fixed)]
+//! #[timestep = "10ms"]
+//! #[max_steps = 4]
+//! #[process(physics)]
+//! struct Physics;
+//!
+//! fn physics(_: &mut Physics, dt: f64, data: &mut DataHelper) {
+//!     // advance the simulation by `dt` seconds
+//! }
+//! # #[derive(SystemManager)] struct Systems {
+//! # physics: conniecs::system::FixedSystem<Physics> }
+//! # fn main() { conniecs::World::<Systems>::new(); }
+//! ```
+//!
+//! After stepping, the fraction of a step left over is published as the
+//! system's interpolation alpha. Rendering systems can read it with
+//! `data.interpolation::<Physics>()` to blend between the last two states.
+
+use std::any::TypeId;
+use std::ops::{Deref, DerefMut};
+
+use crate::entity::EntityData;
+use crate::system::{Process, System};
+use crate::world::DataHelper;
+
+pub trait FixedProcess: SystemTimestep {
+    fn process(&mut self, dt: f64, data: &mut DataHelper<Self::Components, Self::Services>);
+}
+
+pub trait SystemTimestep: System {
+    fn create_timestep() -> Timestep;
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct FixedSystem<T>
+where
+    T: FixedProcess,
+{
+    pub inner: T,
+    pub timestep: Timestep,
+}
+
+impl<T> Deref for FixedSystem<T>
+where
+    T: FixedProcess,
+{
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> DerefMut for FixedSystem<T>
+where
+    T: FixedProcess,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T> System for FixedSystem<T>
+where
+    T: FixedProcess,
+{
+    type Components = T::Components;
+    type Services = T::Services;
+
+    fn build_system() -> Self {
+        FixedSystem {
+            inner: T::build_system(),
+            timestep: T::create_timestep(),
+        }
+    }
+
+    fn activated(
+        &mut self,
+        entity: EntityData<T::Components>,
+        components: &T::Components,
+        services: &mut T::Services,
+    ) {
+        self.inner.activated(entity, components, services);
+    }
+
+    fn reactivated(
+        &mut self,
+        entity: EntityData<T::Components>,
+        components: &T::Components,
+        services: &mut T::Services,
+    ) {
+        self.inner.reactivated(entity, components, services);
+    }
+
+    fn deactivated(
+        &mut self,
+        entity: EntityData<T::Components>,
+        components: &T::Components,
+        services: &mut T::Services,
+    ) {
+        self.inner.deactivated(entity, components, services);
+    }
+}
+
+impl<T> Process for FixedSystem<T>
+where
+    T: FixedProcess + 'static,
+{
+    fn process(&mut self, data: &mut DataHelper<T::Components, T::Services>) {
+        let steps = self.timestep.advance(data.clock.now());
+        let dt = self.timestep.dt();
+        for _ in 0..steps {
+            self.inner.process(dt, data);
+        }
+
+        let alpha = self.timestep.alpha();
+        data.interpolations.insert(TypeId::of::<T>(), alpha);
+    }
+}
+
+/// Accumulates elapsed time and splits it into fixed steps.
+#[derive(Copy, Clone, Debug)]
+pub struct Timestep {
+    /// The length of a step in nanoseconds.
+    pub step: u64,
+    /// The most steps which will be run in a single update.
+    pub max_steps: u32,
+    accumulator: u64,
+    last: Option<u64>,
+}
+
+impl Timestep {
+    pub fn new(step: u64, max_steps: u32) -> Self {
+        assert!(step > 0, "A timestep can't be zero");
+        Timestep {
+            step,
+            max_steps,
+            accumulator: 0,
+            last: None,
+        }
+    }
+
+    /// Adds the time passed since the last call, in nanoseconds, and returns
+    /// how many steps should be run. The first call only starts the clock.
+    pub fn advance(&mut self, now: u64) -> u32 {
+        if let Some(last) = self.last.replace(now) {
+            self.accumulator += now.saturating_sub(last);
+        }
+
+        let due = self.accumulator / self.step;
+        if due > u64::from(self.max_steps) {
+            // Drop the backlog we can't catch up on, keeping the partial step
+            self.accumulator %= self.step;
+            self.max_steps
+        } else {
+            self.accumulator -= due * self.step;
+            due as u32
+        }
+    }
+
+    /// The length of a step in seconds.
+    pub fn dt(&self) -> f64 {
+        self.step as f64 / 1_000_000_000.0
+    }
+
+    /// How far through the next step the accumulated time is, from 0 up to
+    /// but not including 1.
+    pub fn alpha(&self) -> f64 {
+        self.accumulator as f64 / self.step as f64
+    }
+}
@@ -3,9 +3,11 @@
 use std::ops::{Deref, DerefMut};
 
 use crate::aspect::Aspect;
+use crate::component::ComponentManager;
 use crate::entity::{EntityData, EntityIter};
+use crate::services::ServiceManager;
 use crate::system::watcher::Watcher;
-use crate::system::{Process, System};
+use crate::system::{Access, Process, Split, SplitData, SplitProcess, System};
 use crate::world::DataHelper;
 
 pub trait InteractProcess: InteractSystemFilter {
@@ -15,8 +17,30 @@ pub trait InteractProcess: InteractSystemFilter {
         entities_b: EntityIter<'a, Self::Components>,
         data: &mut DataHelper<Self::Components, Self::Services>,
     );
+
+    /// See `Process::split`.
+    fn split(&mut self) -> Option<SplitInteract<'_, Self::Components, Self::Services>> {
+        None
+    }
 }
 
+/// See `SplitProcess`.
+pub trait SplitInteractProcess<C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    fn process_split<'a>(
+        &mut self,
+        entities_a: EntityIter<'a, C>,
+        entities_b: EntityIter<'a, C>,
+        data: &mut SplitData<C, M>,
+    );
+}
+
+/// What `InteractProcess::split` returns.
+pub type SplitInteract<'a, C, M> = (Access, &'a mut dyn SplitInteractProcess<C, M>);
+
 pub trait InteractSystemFilter: System {
     fn create_filter_a() -> Aspect<Self::Components>;
     fn create_filter_b() -> Aspect<Self::Components>;
@@ -112,4 +136,22 @@ where
         let iter_b = self.watcher_b.iter();
         self.inner.process(iter_a, iter_b, data);
     }
+
+    fn split(&mut self) -> Option<Split<'_, T::Components, T::Services>> {
+        let (access, _) = self.inner.split()?;
+        Some((access, self))
+    }
+}
+
+impl<T> SplitProcess<T::Components, T::Services> for InteractSystem<T>
+where
+    T: InteractProcess,
+{
+    fn process_split(&mut self, data: &mut SplitData<T::Components, T::Services>) {
+        if let Some((_, inner)) = self.inner.split() {
+            let iter_a = self.watcher_a.iter();
+            let iter_b = self.watcher_b.iter();
+            inner.process_split(iter_a, iter_b, data);
+        }
+    }
 }
@@ -5,7 +5,7 @@ use time;
 use std::ops::{Deref, DerefMut};
 
 use crate::entity::EntityData;
-use crate::system::{Process, System};
+use crate::system::{Process, Split, SplitData, SplitProcess, System};
 use crate::world::DataHelper;
 
 pub trait SystemInterval: System {
@@ -87,10 +87,28 @@ where
     T: Process + SystemInterval,
 {
     fn process(&mut self, data: &mut DataHelper<T::Components, T::Services>) {
-        if self.ticker.tick() {
+        if self.ticker.tick_at(data.clock.now()) {
             self.inner.process(data);
         }
     }
+
+    fn split(&mut self) -> Option<Split<'_, T::Components, T::Services>> {
+        let (access, _) = self.inner.split()?;
+        Some((access, self))
+    }
+}
+
+impl<T> SplitProcess<T::Components, T::Services> for IntervalSystem<T>
+where
+    T: Process + SystemInterval,
+{
+    fn process_split(&mut self, data: &mut SplitData<T::Components, T::Services>) {
+        if self.ticker.tick_at(data.clock().now()) {
+            if let Some((_, inner)) = self.inner.split() {
+                inner.process_split(data);
+            }
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -107,6 +125,11 @@ pub enum TickerState {
 
 impl TickerState {
     pub fn tick(&mut self) -> bool {
+        self.tick_at(time::precise_time_ns())
+    }
+
+    /// Advances the ticker, with `now` in nanoseconds used by timed intervals.
+    pub fn tick_at(&mut self, now: u64) -> bool {
         match *self {
             TickerState::Frames {
                 interval,
@@ -124,7 +147,6 @@ impl TickerState {
                 interval,
                 ref mut next_tick,
             } => {
-                let now = time::precise_time_ns();
                 let next_tick = match next_tick {
                     &mut Some(ref mut tick) => tick,
                     next_tick => {
@@ -3,7 +3,7 @@
 use std::ops::{Deref, DerefMut};
 
 use crate::entity::EntityData;
-use crate::system::{Process, System};
+use crate::system::{Process, Split, System};
 use crate::world::DataHelper;
 
 pub struct LazySystem<T>
@@ -89,9 +89,9 @@ where
         components: &T::Components,
         services: &mut T::Services,
     ) {
-        self.inner
-            .as_mut()
-            .map(|inner| inner.activated(entity, components, services));
+        if let Some(inner) = self.inner.as_mut() {
+            inner.activated(entity, components, services);
+        }
     }
 
     fn reactivated(
@@ -100,9 +100,9 @@ where
         components: &T::Components,
         services: &mut T::Services,
     ) {
-        self.inner
-            .as_mut()
-            .map(|inner| inner.reactivated(entity, components, services));
+        if let Some(inner) = self.inner.as_mut() {
+            inner.reactivated(entity, components, services);
+        }
     }
 
     fn deactivated(
@@ -111,9 +111,9 @@ where
         components: &T::Components,
         services: &mut T::Services,
     ) {
-        self.inner
-            .as_mut()
-            .map(|inner| inner.deactivated(entity, components, services));
+        if let Some(inner) = self.inner.as_mut() {
+            inner.deactivated(entity, components, services);
+        }
     }
 }
 
@@ -122,6 +122,12 @@ where
     T: Process,
 {
     fn process(&mut self, data: &mut DataHelper<T::Components, T::Services>) {
-        self.inner.as_mut().map(|inner| inner.process(data));
+        if let Some(inner) = self.inner.as_mut() {
+            inner.process(data);
+        }
+    }
+
+    fn split(&mut self) -> Option<Split<'_, T::Components, T::Services>> {
+        self.inner.as_mut()?.split()
     }
 }
@@ -1,4 +1,45 @@
 //! TODO: Add documentation including describing how the derive macros work
+//!
+//! # Ordering
+//!
+//! `#[derive(SystemManager)]` processes systems in field order unless they're
+//! given ordering constraints. `#[before(a, b)]` and `#[after(a, b)]` on a
+//! field run that system before or after the named systems. Stages are
+//! declared in order on the struct with `#[stages(input, logic, render)]`, and
+//! every system marked `#[stage(logic)]` runs after all the `input` systems
+//! and before all the `render` systems. Systems without a stage are only
+//! ordered by their other constraints.
+//!
+//! The order is worked out at compile time, and constraints which form a
+//! cycle are a compile error. Entities are still activated and deactivated in
+//! field order.
+//!
+//! See the `parallel` module for running systems at the same time, and the
+//! `toggle` module for switching systems off at runtime.
+//!
+//! ```
+//! # use conniecs::{ComponentManager, ServiceManager, System, SystemManager};
+//! # use conniecs::system::LazySystem;
+//! # #[derive(ComponentManager)] pub struct Components;
+//! # #[derive(Default, ServiceManager)] pub struct Services;
+//! # #[derive(Default, System)] #[process(process)] pub struct Step;
+//! # fn process(_: &mut Step, _: &mut conniecs::DataHelper<Components, Services>) {}
+//! #[derive(SystemManager)]
+//! #[stages(input, logic, render)]
+//! pub struct Systems {
+//!     #[stage(render)]
+//!     draw: Step,
+//!     #[stage(logic)]
+//!     #[after(physics)]
+//!     collisions: Step,
+//!     #[stage(logic)]
+//!     physics: Step,
+//!     #[stage(input)]
+//!     keyboard: Step,
+//! }
+//! // Runs keyboard, physics, collisions, draw
+//! # fn main() {}
+//! ```
 
 use crate::component::ComponentManager;
 use crate::entity::EntityData;
@@ -6,14 +47,20 @@ use crate::services::ServiceManager;
 use crate::world::DataHelper;
 
 pub use crate::system::entity::{EntityProcess, EntitySystem};
+pub use crate::system::fixed::{FixedProcess, FixedSystem};
 pub use crate::system::interact::{InteractProcess, InteractSystem};
 pub use crate::system::interval::IntervalSystem;
 pub use crate::system::lazy::LazySystem;
+pub use crate::system::parallel::{Access, Split, SplitData, SplitMut, SplitProcess, SplitRef};
+pub use crate::system::toggle::ToggleSystem;
 
 pub mod entity;
+pub mod fixed;
 pub mod interact;
 pub mod interval;
 pub mod lazy;
+pub mod parallel;
+pub mod toggle;
 pub mod watcher;
 
 pub trait System {
@@ -52,10 +99,24 @@ pub trait System {
     ) {
         let (_, _, _) = (entity, components, services);
     }
+
+    #[doc(hidden)]
+    fn __set_enabled(&mut self, enabled: bool) -> bool {
+        let _ = enabled;
+        false
+    }
 }
 
 pub trait Process: System {
     fn process(&mut self, data: &mut DataHelper<Self::Components, Self::Services>);
+
+    /// The lists and services this system declared with `#[reads]` and
+    /// `#[writes]`, along with the system as something which can process
+    /// through a `SplitData` of only those. `None` if it needs the whole
+    /// `DataHelper`.
+    fn split(&mut self) -> Option<Split<'_, Self::Components, Self::Services>> {
+        None
+    }
 }
 
 pub trait SystemManager {
@@ -87,6 +148,10 @@ pub trait SystemManager {
 
     fn update(&mut self, data: &mut DataHelper<Self::Components, Self::Services>);
 
+    /// Enables or disables the system in the field called `name`. Returns
+    /// false if there's no such field or it isn't a `ToggleSystem`.
+    fn set_enabled(&mut self, name: &str, enabled: bool) -> bool;
+
     #[doc(hidden)]
     fn __please_use_the_derive_attribute();
 }
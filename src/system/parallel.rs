@@ -0,0 +1,576 @@
+//! Running systems side by side.
+//!
+//! A system can declare the component lists and services it uses with
+//! `#[reads(...)]` and `#[writes(...)]` on its `#[derive(System)]`. Services
+//! go in a nested `services(...)` list, and `resources` stands for the
+//! world's `Resources` rather than a component list.
+//!
+//! ```ignore
+//! #[derive(Default, System)]
+//! #[system_type(entity)]
+//! #[aspect(all(position, velocity))]
+//! #[reads(velocity, services(clock))]
+//! #[writes(position)]
+//! #[process(movement)]
+//! pub struct Movement;
+//! ```
+//!
+//! Every name declared has to be a field of the component or service manager,
+//! which is checked when the system is compiled.
+//!
+//! Its process function then takes a `SplitData` instead of a `DataHelper`.
+//! A `SplitData` only hands out the lists and services which were declared,
+//! so systems whose accesses don't overlap can't see each other's data. They
+//! are looked up by name, and handed out behind guards which give the borrow
+//! back when dropped.
+//! Structural edits, including changes to parents, go through the view's own
+//! `commands`, which are added to the world's queue in system order once the
+//! system has finished. The hierarchy and entity events can still be read.
+//!
+//! Marking a `#[derive(SystemManager)]` with `#[parallel]` runs systems whose
+//! accesses don't conflict at the same time, on the rayon thread pool when
+//! the `rayon` feature is enabled. The ordering from `#[before]`, `#[after]`
+//! and `#[stage]` is kept, and systems which don't declare their access still
+//! run on their own with the whole `DataHelper`. Without `#[parallel]`,
+//! systems with declared access run in order through a `SplitData` like any
+//! other.
+//!
+//! Every system, component list and service has to be `Send + Sync` for a
+//! manager to be `#[parallel]`.
+
+use fnv::FnvHashMap;
+
+use std::any::{self, Any, TypeId};
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+
+use crate::clock::Clock;
+use crate::commands::Commands;
+use crate::component::{Component, ComponentList, ComponentManager};
+use crate::entity::{Entity, EntityEvent, EntityIter, EntityManager, Hierarchy};
+use crate::event::EventChannel;
+use crate::resources::Resources;
+use crate::services::ServiceManager;
+use crate::system::Process;
+use crate::tracking::Tick;
+use crate::world::DataHelper;
+
+/// The component lists and services a system reads and writes, by field
+/// name, and whether it uses the world's `Resources`. Writing to something
+/// implies being able to read it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Access {
+    pub reads: &'static [&'static str],
+    pub writes: &'static [&'static str],
+    pub reads_services: &'static [&'static str],
+    pub writes_services: &'static [&'static str],
+    pub reads_resources: bool,
+    pub writes_resources: bool,
+}
+
+impl Access {
+    /// Returns true if the two systems can't safely run at the same time.
+    pub fn conflicts_with(&self, other: &Access) -> bool {
+        fn overlaps(a: &[&str], b: &[&str]) -> bool {
+            a.iter().any(|name| b.contains(name))
+        }
+
+        overlaps(self.writes, other.reads)
+            || overlaps(self.writes, other.writes)
+            || overlaps(self.reads, other.writes)
+            || overlaps(self.writes_services, other.reads_services)
+            || overlaps(self.writes_services, other.writes_services)
+            || overlaps(self.reads_services, other.writes_services)
+            || (self.writes_resources && (other.reads_resources || other.writes_resources))
+            || (self.reads_resources && other.writes_resources)
+    }
+
+    fn declared(&self, kind: Kind) -> (&'static [&'static str], &'static [&'static str]) {
+        fn resources(declared: bool) -> &'static [&'static str] {
+            if declared {
+                &["resources"]
+            } else {
+                &[]
+            }
+        }
+
+        match kind {
+            Kind::List => (self.reads, self.writes),
+            Kind::Service => (self.reads_services, self.writes_services),
+            Kind::Resources => (
+                resources(self.reads_resources),
+                resources(self.writes_resources),
+            ),
+        }
+    }
+}
+
+/// A system which processes through a `SplitData`. Implemented by
+/// `#[derive(System)]` for systems which declare their access, and handed to
+/// the world by `Process::split`.
+pub trait SplitProcess<C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    fn process_split(&mut self, data: &mut SplitData<C, M>);
+}
+
+/// What `Process::split` returns: a system's access, and the system to run
+/// through a view of it.
+pub type Split<'a, C, M> = (Access, &'a mut dyn SplitProcess<C, M>);
+
+/// A view into the world which only gives out the component lists and
+/// services a system declared in its `Access`.
+///
+/// Borrows are checked at runtime, and last until the guard handed out for
+/// them is dropped. Asking for anything which wasn't declared, or for a
+/// mutable borrow of something already borrowed through this view, panics.
+pub struct SplitData<'a, C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    /// Structural edits queued by this system.
+    pub commands: Commands<C, M>,
+    components: *mut C,
+    services: *mut M,
+    resources: *mut Resources,
+    entities: &'a EntityManager<C>,
+    entity_events: &'a EventChannel<EntityEvent>,
+    tick: Tick,
+    interpolations: &'a FnvHashMap<TypeId, f64>,
+    clock: &'a Clock,
+    access: Access,
+    borrows: &'a RefCell<Vec<Borrow>>,
+    _marker: PhantomData<(&'a mut C, &'a mut M)>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Kind {
+    List,
+    Service,
+    Resources,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+struct Borrow {
+    kind: Kind,
+    name: &'static str,
+    mutable: bool,
+}
+
+/// Gives a borrow back to its `SplitData` when dropped.
+struct Release<'a> {
+    borrows: &'a RefCell<Vec<Borrow>>,
+    borrow: Borrow,
+}
+
+impl Drop for Release<'_> {
+    fn drop(&mut self) {
+        let mut borrows = self.borrows.borrow_mut();
+        if let Some(i) = borrows.iter().position(|b| *b == self.borrow) {
+            borrows.swap_remove(i);
+        }
+    }
+}
+
+/// Shared access to a component list, service or the resources, borrowed
+/// from a `SplitData`.
+pub struct SplitRef<'a, T> {
+    value: &'a T,
+    _release: Release<'a>,
+}
+
+impl<T> Deref for SplitRef<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+/// Mutable access to a component list, service or the resources, borrowed
+/// from a `SplitData`.
+pub struct SplitMut<'a, T> {
+    value: &'a mut T,
+    _release: Release<'a>,
+}
+
+impl<T> Deref for SplitMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T> DerefMut for SplitMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+    }
+}
+
+// Safety: the view only hands out the parts of the world named in its access,
+// which the scheduler keeps from overlapping between threads. Its borrows are
+// never shared with another view, and the guards holding onto them can't be
+// sent to another thread.
+unsafe impl<C, M> Send for SplitData<'_, C, M>
+where
+    C: ComponentManager + Send + Sync,
+    M: ServiceManager + Send + Sync,
+{
+}
+
+impl<'a, C, M> SplitData<'a, C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    /// `data` must stay valid for `'a`. Nothing else may touch the parts of
+    /// its components and services named in `access` for that long, or
+    /// change anything else in it but its `commands`. `borrows` must start
+    /// out empty and not be shared with another view.
+    unsafe fn new(
+        data: *mut DataHelper<C, M>,
+        access: Access,
+        borrows: &'a RefCell<Vec<Borrow>>,
+    ) -> Self {
+        SplitData {
+            commands: Commands::new(),
+            components: ptr::addr_of_mut!((*data).components),
+            services: ptr::addr_of_mut!((*data).services),
+            resources: ptr::addr_of_mut!((*data).resources),
+            entities: &(*data).entities,
+            entity_events: &(*data).entity_events,
+            tick: (*data).tick,
+            interpolations: &(*data).interpolations,
+            clock: &(*data).clock,
+            access,
+            borrows,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn access(&self) -> Access {
+        self.access
+    }
+
+    pub fn read<T>(&self, list: &str) -> SplitRef<'a, ComponentList<C, T>>
+    where
+        T: Component,
+    {
+        let release = self.borrow(Kind::List, list, false);
+        let ptr = unsafe { C::__list_ptr(self.components, list) };
+        let ptr = ptr.unwrap_or_else(|| panic!("There is no component list named `{}`", list));
+        let list_ref: &'a dyn Any = unsafe { &*ptr };
+        SplitRef {
+            value: list_ref
+                .downcast_ref()
+                .unwrap_or_else(|| wrong_type::<ComponentList<C, T>>(list)),
+            _release: release,
+        }
+    }
+
+    // Borrows are tracked by the view, so handing out mutable guards from
+    // `&self` lets systems hold several lists at once.
+    pub fn write<T>(&self, list: &str) -> SplitMut<'a, ComponentList<C, T>>
+    where
+        T: Component,
+    {
+        let release = self.borrow(Kind::List, list, true);
+        let ptr = unsafe { C::__list_ptr(self.components, list) };
+        let ptr = ptr.unwrap_or_else(|| panic!("There is no component list named `{}`", list));
+        let list_ref: &'a mut dyn Any = unsafe { &mut *ptr };
+        SplitMut {
+            value: list_ref
+                .downcast_mut()
+                .unwrap_or_else(|| wrong_type::<ComponentList<C, T>>(list)),
+            _release: release,
+        }
+    }
+
+    pub fn service<T>(&self, name: &str) -> SplitRef<'a, T>
+    where
+        T: 'static,
+    {
+        let release = self.borrow(Kind::Service, name, false);
+        let ptr = unsafe { M::__service_ptr(self.services, name) };
+        let ptr = ptr.unwrap_or_else(|| panic!("There is no service named `{}`", name));
+        let service: &'a dyn Any = unsafe { &*ptr };
+        SplitRef {
+            value: service
+                .downcast_ref()
+                .unwrap_or_else(|| wrong_type::<T>(name)),
+            _release: release,
+        }
+    }
+
+    pub fn service_mut<T>(&self, name: &str) -> SplitMut<'a, T>
+    where
+        T: 'static,
+    {
+        let release = self.borrow(Kind::Service, name, true);
+        let ptr = unsafe { M::__service_ptr(self.services, name) };
+        let ptr = ptr.unwrap_or_else(|| panic!("There is no service named `{}`", name));
+        let service: &'a mut dyn Any = unsafe { &mut *ptr };
+        SplitMut {
+            value: service
+                .downcast_mut()
+                .unwrap_or_else(|| wrong_type::<T>(name)),
+            _release: release,
+        }
+    }
+
+    /// The world's `Resources`, if the system declared `resources` in its
+    /// `#[reads]`.
+    pub fn resources(&self) -> SplitRef<'a, Resources> {
+        SplitRef {
+            _release: self.borrow(Kind::Resources, "resources", false),
+            value: unsafe { &*self.resources },
+        }
+    }
+
+    pub fn resources_mut(&self) -> SplitMut<'a, Resources> {
+        SplitMut {
+            _release: self.borrow(Kind::Resources, "resources", true),
+            value: unsafe { &mut *self.resources },
+        }
+    }
+
+    pub fn entities(&self) -> EntityIter<'a, C> {
+        self.entities.iter()
+    }
+
+    pub fn is_valid(&self, entity: Entity) -> bool {
+        self.entities.is_valid(entity)
+    }
+
+    /// See `DataHelper::hierarchy`. Parents are changed through `commands`.
+    pub fn hierarchy(&self) -> &'a Hierarchy {
+        self.entities.hierarchy()
+    }
+
+    /// See `DataHelper::entity_events`.
+    pub fn entity_events(&self) -> &'a EventChannel<EntityEvent> {
+        self.entity_events
+    }
+
+    #[inline]
+    pub fn tick(&self) -> Tick {
+        self.tick
+    }
+
+    pub fn clock(&self) -> &'a Clock {
+        self.clock
+    }
+
+    /// See `DataHelper::interpolation`.
+    pub fn interpolation<S>(&self) -> Option<f64>
+    where
+        S: 'static,
+    {
+        self.interpolations.get(&TypeId::of::<S>()).copied()
+    }
+
+    fn borrow(&self, kind: Kind, name: &str, mutable: bool) -> Release<'a> {
+        let (reads, writes) = self.access.declared(kind);
+        let readable = if mutable { &[][..] } else { reads };
+        let name = *writes
+            .iter()
+            .chain(readable)
+            .find(|&&declared| declared == name)
+            .unwrap_or_else(|| {
+                panic!(
+                    "This system didn't declare that it {} `{}`",
+                    if mutable { "writes" } else { "reads" },
+                    name
+                )
+            });
+
+        let mut borrows = self.borrows.borrow_mut();
+        let taken = borrows
+            .iter()
+            .any(|b| b.kind == kind && b.name == name && (b.mutable || mutable));
+        if taken {
+            panic!("`{}` is already borrowed", name);
+        }
+        let borrow = Borrow {
+            kind,
+            name,
+            mutable,
+        };
+        borrows.push(borrow);
+        Release {
+            borrows: self.borrows,
+            borrow,
+        }
+    }
+}
+
+fn wrong_type<T>(name: &str) -> ! {
+    panic!("`{}` is not a {}", name, any::type_name::<T>())
+}
+
+impl<C, M> DataHelper<C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    /// Runs a system's process function through a view of the data it
+    /// declared.
+    #[doc(hidden)]
+    pub fn __split<F, R>(&mut self, access: Access, f: F) -> R
+    where
+        F: FnOnce(&mut SplitData<C, M>) -> R,
+    {
+        let borrows = RefCell::new(Vec::new());
+        let mut split = unsafe { SplitData::new(self, access, &borrows) };
+        let result = f(&mut split);
+        self.commands.append(mem::take(&mut split.commands));
+        result
+    }
+}
+
+/// The parts of `Process` a batch needs, without the constructor that keeps
+/// it from being made into a trait object.
+#[doc(hidden)]
+pub trait __Batched<C, M>: Send
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    fn access(&mut self) -> Option<Access>;
+    fn process(&mut self, data: &mut DataHelper<C, M>);
+    fn process_split(&mut self, data: &mut SplitData<C, M>);
+}
+
+impl<T> __Batched<T::Components, T::Services> for T
+where
+    T: Process + Send,
+{
+    fn access(&mut self) -> Option<Access> {
+        Process::split(self).map(|(access, _)| access)
+    }
+
+    fn process(&mut self, data: &mut DataHelper<T::Components, T::Services>) {
+        Process::process(self, data)
+    }
+
+    fn process_split(&mut self, data: &mut SplitData<T::Components, T::Services>) {
+        if let Some((_, system)) = Process::split(self) {
+            system.process_split(data);
+        }
+    }
+}
+
+type BatchedSystem<'a, C, M> = &'a mut dyn __Batched<C, M>;
+type Batch<'a, 'b, C, M> = [(BatchedSystem<'a, C, M>, SplitData<'b, C, M>)];
+
+/// Groups systems into batches which can run at the same time. Used by
+/// `#[parallel]` system managers.
+#[doc(hidden)]
+pub struct __Batches<'a, C, M>
+where
+    C: ComponentManager,
+    M: ServiceManager,
+{
+    data: &'a mut DataHelper<C, M>,
+    batch: Vec<(usize, BatchedSystem<'a, C, M>, Access)>,
+}
+
+impl<'a, C, M> __Batches<'a, C, M>
+where
+    C: ComponentManager + Send + Sync,
+    M: ServiceManager + Send + Sync,
+{
+    pub fn new(data: &'a mut DataHelper<C, M>) -> Self {
+        __Batches {
+            data,
+            batch: Vec::new(),
+        }
+    }
+
+    /// Queues the system with the given field index, which must run after
+    /// the systems in `after`.
+    pub fn run(&mut self, index: usize, system: BatchedSystem<'a, C, M>, after: &[usize]) {
+        let access = match system.access() {
+            Some(access) => access,
+            None => {
+                self.flush();
+                system.process(self.data);
+                return;
+            }
+        };
+
+        let blocked = self
+            .batch
+            .iter()
+            .any(|(i, _, other)| after.contains(i) || access.conflicts_with(other));
+        if blocked {
+            self.flush();
+        }
+        self.batch.push((index, system, access));
+    }
+
+    pub fn finish(mut self) {
+        self.flush();
+    }
+
+    fn flush(&mut self) {
+        if self.batch.is_empty() {
+            return;
+        }
+
+        let data: *mut DataHelper<C, M> = &mut *self.data;
+        let borrows: Vec<_> = self.batch.iter().map(|_| RefCell::default()).collect();
+        let mut batch: Vec<_> = self
+            .batch
+            .drain(..)
+            .zip(&borrows)
+            .map(|((_, system, access), borrows)| {
+                let split = unsafe { SplitData::new(data, access, borrows) };
+                (system, split)
+            })
+            .collect();
+
+        run_batch(&mut batch);
+
+        let queued: Vec<_> = batch.into_iter().map(|(_, split)| split.commands).collect();
+        for commands in queued {
+            self.data.commands.append(commands);
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+fn run_batch<C, M>(batch: &mut Batch<C, M>)
+where
+    C: ComponentManager + Send + Sync,
+    M: ServiceManager + Send + Sync,
+{
+    if let [(system, split)] = batch {
+        system.process_split(split);
+        return;
+    }
+
+    rayon::scope(|scope| {
+        for (system, split) in batch.iter_mut() {
+            scope.spawn(move |_| system.process_split(split));
+        }
+    });
+}
+
+#[cfg(not(feature = "rayon"))]
+fn run_batch<C, M>(batch: &mut Batch<C, M>)
+where
+    C: ComponentManager + Send + Sync,
+    M: ServiceManager + Send + Sync,
+{
+    for (system, split) in batch.iter_mut() {
+        system.process_split(split);
+    }
+}
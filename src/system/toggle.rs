@@ -0,0 +1,177 @@
+//! Systems which can be switched off and on while the world is running.
+//!
+//! Wrapping a system in `ToggleSystem` lets it be disabled, either directly
+//! or by name through `SystemManager::set_enabled`. A disabled system isn't
+//! processed, but by default it's still told about entities being activated
+//! and deactivated, so its watcher is up to date when it's enabled again.
+//! With `watch_while_disabled` turned off, the system hears nothing but
+//! removals while disabled and `World::refresh` has to be called to bring it
+//! back in sync. Removed entities are always passed on, since a refresh only
+//! goes over the entities which are still alive.
+//!
+//! ```
+//! # #[macro_use] extern crate conniecs_derive; extern crate conniecs;
+//! # use conniecs::system::{SystemManager, ToggleSystem};
+//! # #[derive(ComponentManager)] struct Components;
+//! # #[derive(ServiceManager, Default)] struct Services {}
+//! # #[derive(Default, System)] #[process(process)] struct Physics;
+//! # fn process(_: &mut Physics, _: &mut conniecs::DataHelper<Components, Services>) {}
+//! #[derive(SystemManager)]
+//! struct Systems {
+//!     physics: ToggleSystem<Physics>,
+//! }
+//!
+//! # fn main() {
+//! let mut world = conniecs::World::<Systems>::new();
+//! world.systems.set_enabled("physics", false);
+//! assert!(!world.systems.physics.is_enabled());
+//! # }
+//! ```
+
+use std::ops::{Deref, DerefMut};
+
+use crate::entity::EntityData;
+use crate::system::{Process, Split, SplitData, SplitProcess, System};
+use crate::world::DataHelper;
+
+pub struct ToggleSystem<T>
+where
+    T: System,
+{
+    pub inner: T,
+    /// Whether activations still reach the system while it's disabled.
+    /// Removals always reach it.
+    pub watch_while_disabled: bool,
+    enabled: bool,
+}
+
+impl<T> Deref for ToggleSystem<T>
+where
+    T: System,
+{
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> DerefMut for ToggleSystem<T>
+where
+    T: System,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T> ToggleSystem<T>
+where
+    T: System,
+{
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    #[inline]
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    #[inline]
+    pub fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    #[inline]
+    pub fn disable(&mut self) {
+        self.enabled = false;
+    }
+
+    #[inline]
+    fn watching(&self) -> bool {
+        self.enabled || self.watch_while_disabled
+    }
+}
+
+impl<T> System for ToggleSystem<T>
+where
+    T: System,
+{
+    type Components = T::Components;
+    type Services = T::Services;
+
+    fn build_system() -> Self {
+        ToggleSystem {
+            inner: T::build_system(),
+            watch_while_disabled: true,
+            enabled: true,
+        }
+    }
+
+    fn activated(
+        &mut self,
+        entity: EntityData<T::Components>,
+        components: &T::Components,
+        services: &mut T::Services,
+    ) {
+        if self.watching() {
+            self.inner.activated(entity, components, services);
+        }
+    }
+
+    fn reactivated(
+        &mut self,
+        entity: EntityData<T::Components>,
+        components: &T::Components,
+        services: &mut T::Services,
+    ) {
+        if self.watching() {
+            self.inner.reactivated(entity, components, services);
+        }
+    }
+
+    fn deactivated(
+        &mut self,
+        entity: EntityData<T::Components>,
+        components: &T::Components,
+        services: &mut T::Services,
+    ) {
+        self.inner.deactivated(entity, components, services);
+    }
+
+    #[doc(hidden)]
+    fn __set_enabled(&mut self, enabled: bool) -> bool {
+        self.enabled = enabled;
+        true
+    }
+}
+
+impl<T> Process for ToggleSystem<T>
+where
+    T: Process,
+{
+    fn process(&mut self, data: &mut DataHelper<T::Components, T::Services>) {
+        if self.enabled {
+            self.inner.process(data);
+        }
+    }
+
+    fn split(&mut self) -> Option<Split<'_, T::Components, T::Services>> {
+        let (access, _) = self.inner.split()?;
+        Some((access, self))
+    }
+}
+
+impl<T> SplitProcess<T::Components, T::Services> for ToggleSystem<T>
+where
+    T: Process,
+{
+    fn process_split(&mut self, data: &mut SplitData<T::Components, T::Services>) {
+        if self.enabled {
+            if let Some((_, inner)) = self.inner.split() {
+                inner.process_split(data);
+            }
+        }
+    }
+}
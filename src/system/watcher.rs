@@ -24,7 +24,7 @@ impl<C> Watcher<C>
 where
     C: ComponentManager,
 {
-    pub fn iter(&self) -> EntityIter<C> {
+    pub fn iter(&self) -> EntityIter<'_, C> {
         let watched = WatchedEntityIter {
             current_range: Range { min: 1, max: 0 },
             indices: self.ranges.free_ranges(),
@@ -84,7 +84,11 @@ where
             self.interested.contains_key(&entity.index()),
             self.aspect.check(entity, components),
         ) {
-            (true, true) => inner.reactivated(entity, components, services),
+            (true, true) => {
+                // Keep our copy's signature in sync
+                self.interested.insert(entity.index(), entity.__clone());
+                inner.reactivated(entity, components, services);
+            }
             (true, false) => {
                 self.interested.remove(&entity.index());
                 self.ranges.set_used(entity.index());
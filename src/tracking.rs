@@ -0,0 +1,162 @@
+//! Recording which components were added, modified or removed.
+//!
+//! Mark a list with `#[tracked]` in your `ComponentManager` to have it
+//! remember the tick at which each entity's component last changed. The
+//! world's tick advances at the end of every `World::update`, so
+//! `changes_since(data.tick())` finds the changes made so far in the current
+//! update: those flushed at its start and those made by systems which ran
+//! earlier in it.
+//!
+//! Changes made by systems which run later in the update get the same tick,
+//! so a system reading that way misses them. Either order it after the
+//! systems which write to the list, or read
+//! `changes_since(data.tick().saturating_sub(1))` to pick them up on the next
+//! update, which also repeats the changes it saw last time. The tick starts
+//! at 0, so plain subtraction would overflow during the first update.
+//!
+//! Access through `borrow`, `IndexMut`, `iter_mut` and mutable joins counts
+//! as a modification whether or not the component was actually written to.
+//!
+//! Changes are kept until they are cleared with `clear_changes` or
+//! `clear_changes_before`, so something should be clearing them regularly.
+
+use fnv::FnvHashMap;
+
+use std::collections::hash_map;
+
+use crate::entity::Entity;
+
+/// Counts the number of times the world has been updated.
+pub type Tick = u64;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Change {
+    Added,
+    Modified,
+    Removed,
+}
+
+/// When a change was last made: its tick, and its place among every change
+/// the tracker has recorded.
+#[derive(Copy, Clone, Debug)]
+struct Stamp {
+    tick: Tick,
+    order: u64,
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct Record {
+    added: Option<Stamp>,
+    modified: Option<Stamp>,
+    removed: Option<Stamp>,
+}
+
+impl Record {
+    fn get(&self, change: Change) -> Option<Stamp> {
+        match change {
+            Change::Added => self.added,
+            Change::Modified => self.modified,
+            Change::Removed => self.removed,
+        }
+    }
+
+    fn set(&mut self, change: Change, stamp: Stamp) {
+        let slot = match change {
+            Change::Added => &mut self.added,
+            Change::Modified => &mut self.modified,
+            Change::Removed => &mut self.removed,
+        };
+        *slot = Some(stamp);
+    }
+
+    /// Forgets changes from before the tick, returning true if none are left.
+    fn retain_since(&mut self, tick: Tick) -> bool {
+        for slot in [&mut self.added, &mut self.modified, &mut self.removed] {
+            if slot.is_some_and(|s| s.tick < tick) {
+                *slot = None;
+            }
+        }
+        self.added.is_none() && self.modified.is_none() && self.removed.is_none()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ChangeTracker {
+    tick: Tick,
+    recorded: u64,
+    records: FnvHashMap<Entity, Record>,
+}
+
+impl ChangeTracker {
+    #[inline]
+    pub(crate) fn record(&mut self, entity: Entity, change: Change) {
+        let stamp = Stamp {
+            tick: self.tick,
+            order: self.recorded,
+        };
+        self.recorded += 1;
+        self.records.entry(entity).or_default().set(change, stamp);
+    }
+
+    pub(crate) fn set_tick(&mut self, tick: Tick) {
+        self.tick = tick;
+    }
+
+    pub(crate) fn changes_since(&self, tick: Tick) -> Changes<'_> {
+        Changes {
+            records: self.records.iter(),
+            current: None,
+            pending: [None; 3],
+            next: 3,
+            since: tick,
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.records.clear();
+    }
+
+    pub(crate) fn clear_before(&mut self, tick: Tick) {
+        self.records.retain(|_, record| !record.retain_since(tick));
+    }
+}
+
+/// Iterates over the changes made to a tracked list since some tick. Each
+/// entity is visited once for each kind of change, in the order those
+/// changes were last made, so the last change of an entity always matches
+/// whether it still has the component. A component removed and added again
+/// comes out as removed, then added.
+pub struct Changes<'a> {
+    records: hash_map::Iter<'a, Entity, Record>,
+    current: Option<Entity>,
+    pending: [Option<(u64, Change)>; 3],
+    next: usize,
+    since: Tick,
+}
+
+impl<'a> Iterator for Changes<'a> {
+    type Item = (Entity, Change);
+
+    fn next(&mut self) -> Option<(Entity, Change)> {
+        const KINDS: [Change; 3] = [Change::Added, Change::Modified, Change::Removed];
+        loop {
+            while let Some(&pending) = self.pending.get(self.next) {
+                self.next += 1;
+                if let (Some(entity), Some((_, change))) = (self.current, pending) {
+                    return Some((entity, change));
+                }
+            }
+
+            let (&entity, record) = self.records.next()?;
+            let since = self.since;
+            self.pending = KINDS.map(|change| {
+                let stamp = record.get(change).filter(|s| s.tick >= since)?;
+                Some((stamp.order, change))
+            });
+            // Changes which weren't made sort first and are skipped
+            self.pending.sort();
+            self.current = Some(entity);
+            self.next = 0;
+        }
+    }
+}
@@ -1,12 +1,24 @@
 //! TODO: Add documentation including describing how the derive macros work
 
-use crate::component::ComponentManager;
+use fnv::FnvHashMap;
+
+use std::any::TypeId;
+
+use crate::clock::Clock;
+use crate::commands::Commands;
+use crate::component::{Component, ComponentList, ComponentManager};
 use crate::entity::{
-    BuildData, Entity, EntityBuilder, EntityData, EntityIter, EntityManager, EntityModifier,
-    ModifyData,
+    BuildData, Entity, EntityBuilder, EntityData, EntityEvent, EntityIter, EntityManager,
+    EntityModifier, Hierarchy, ModifyData, Prefab,
 };
+use crate::event::EventChannel;
+use crate::resources::Resources;
+#[cfg(feature = "serde")]
+use crate::serialize::{LoadedWorld, SavedWorld, SerializeComponents};
 use crate::services::ServiceManager;
+use crate::snapshot::Snapshot;
 use crate::system::SystemManager;
+use crate::tracking::Tick;
 
 pub struct World<S>
 where
@@ -23,7 +35,16 @@ where
 {
     pub components: C,
     pub services: M,
+    /// Structural edits queued up to be applied when the world next flushes.
+    pub commands: Commands<C, M>,
+    /// The time seen by timed systems. See the `clock` module.
+    pub clock: Clock,
+    /// Values stored by type. See the `resources` module.
+    pub resources: Resources,
     pub(crate) entities: EntityManager<C>,
+    pub(crate) entity_events: EventChannel<EntityEvent>,
+    pub(crate) tick: Tick,
+    pub(crate) interpolations: FnvHashMap<TypeId, f64>,
 }
 
 impl<C, M> DataHelper<C, M>
@@ -35,15 +56,18 @@ where
     where
         F: FnOnce(EntityData<C>, &mut C, &mut M) -> R,
     {
-        if self.entities.is_valid(entity) {
-            Some(closure(
-                EntityData(self.entities.indexed(entity)),
-                &mut self.components,
-                &mut self.services,
-            ))
-        } else {
-            None
-        }
+        let indexed = self.entities.get(entity)?;
+        Some(closure(
+            EntityData(indexed),
+            &mut self.components,
+            &mut self.services,
+        ))
+    }
+
+    /// Returns true if the entity has not been removed. Handles to removed
+    /// entities stay invalid even after their slot is reused.
+    pub fn is_valid(&self, entity: Entity) -> bool {
+        self.entities.is_valid(entity)
     }
 
     pub fn create_entity<F>(&mut self, builder: F) -> Entity
@@ -61,13 +85,123 @@ where
             .create_entity(builder, &mut self.components, &mut self.services)
     }
 
+    /// Spawns `count` entities from a prefab. See the `entity::prefab`
+    /// module.
+    pub fn instantiate(&mut self, prefab: &Prefab<C, M>, count: usize) -> Vec<Entity> {
+        (0..count)
+            .map(|_| self.create_entity_with_builder(prefab))
+            .collect()
+    }
+
+    /// Creates a copy of an entity with every component it has whose type
+    /// is `Clone`. Returns `None` if the entity isn't valid.
+    pub fn clone_entity(&mut self, entity: Entity) -> Option<Entity> {
+        self.entities
+            .clone_entity(entity, &mut self.components, &mut self.services)
+    }
+
+    /// Copies an entity along with all of its descendants in the hierarchy.
+    /// The copy of `entity` itself has no parent.
+    pub fn clone_entity_tree(&mut self, entity: Entity) -> Option<Entity> {
+        self.entities
+            .clone_entity_tree(entity, &mut self.components, &mut self.services)
+    }
+
+    /// Applies a modification to an entity immediately. The systems are told
+    /// about it the next time the world flushes its queues, which is when
+    /// watchers pick up any change in which aspects the entity matches.
+    /// Returns false if the entity is no longer valid.
+    pub fn modify_entity<F>(&mut self, entity: Entity, modifier: F) -> bool
+    where
+        F: FnOnce(ModifyData<C>, &mut C, &mut M),
+    {
+        self.modify_entity_with_modifier(entity, modifier)
+    }
+
+    pub fn modify_entity_with_modifier<E>(&mut self, entity: Entity, modifier: E) -> bool
+    where
+        E: EntityModifier<C, M>,
+    {
+        self.entities
+            .modify_entity(entity, modifier, &mut self.components, &mut self.services)
+    }
+
+    /// Queues an entity to be removed along with all of its descendants.
     pub fn remove_entity(&mut self, entity: Entity) -> bool {
         self.entities.remove_entity(entity)
     }
 
-    pub fn entities(&self) -> EntityIter<C> {
+    /// Parent and child links between entities. See the `entity::hierarchy`
+    /// module.
+    pub fn hierarchy(&self) -> &Hierarchy {
+        self.entities.hierarchy()
+    }
+
+    /// Makes `parent` the parent of `child`, replacing any parent it had.
+    /// Returns false if either entity isn't valid, `child` is an ancestor of
+    /// `parent`, or `parent` is queued to be removed.
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) -> bool {
+        self.entities.set_parent(child, parent)
+    }
+
+    /// Detaches an entity from its parent, returning the parent it had.
+    pub fn clear_parent(&mut self, child: Entity) -> Option<Entity> {
+        self.entities.clear_parent(child)
+    }
+
+    /// Removes every component from the list picked out by `list`, returning
+    /// them along with the entities they belonged to. The affected entities
+    /// are reactivated the next time the world flushes its queues.
+    pub fn drain_components<T, F>(&mut self, list: F) -> Vec<(Entity, T)>
+    where
+        T: Component,
+        F: FnOnce(&mut C) -> &mut ComponentList<C, T>,
+    {
+        let list = list(&mut self.components);
+        let drained = list.drain();
+        let bit = list.signature_bit();
+        self.entities
+            .components_removed(drained.iter().map(|&(entity, _)| entity), bit);
+        drained
+    }
+
+    pub fn entities(&self) -> EntityIter<'_, C> {
         self.entities.iter()
     }
+
+    /// What has happened to entities over this update and the last. See the
+    /// `entity::lifecycle` module.
+    pub fn entity_events(&self) -> &EventChannel<EntityEvent> {
+        &self.entity_events
+    }
+
+    /// The number of times the world has been updated. Changes to tracked
+    /// component lists are recorded against this, so everything done between
+    /// the end of the last update and the end of the next one shares a tick.
+    #[inline]
+    pub fn tick(&self) -> Tick {
+        self.tick
+    }
+
+    /// The interpolation alpha published by the fixed timestep system `S`
+    /// the last time it ran, or `None` if it hasn't run yet. See the
+    /// `system::fixed` module.
+    pub fn interpolation<S>(&self) -> Option<f64>
+    where
+        S: 'static,
+    {
+        self.interpolations.get(&TypeId::of::<S>()).copied()
+    }
+}
+
+impl<S> Default for World<S>
+where
+    S: SystemManager,
+    S::Services: Default,
+{
+    fn default() -> Self {
+        World::new()
+    }
 }
 
 impl<S> World<S>
@@ -87,12 +221,18 @@ where
             data: DataHelper {
                 services,
                 components: S::Components::build_manager(),
+                commands: Commands::new(),
+                clock: Clock::new(),
+                resources: Resources::new(),
                 entities: EntityManager::new(),
+                entity_events: EventChannel::new(),
+                tick: 0,
+                interpolations: FnvHashMap::default(),
             },
         }
     }
 
-    pub fn entities(&self) -> EntityIter<S::Components> {
+    pub fn entities(&self) -> EntityIter<'_, S::Components> {
         self.data.entities.iter()
     }
 
@@ -100,19 +240,30 @@ where
     where
         F: FnOnce(ModifyData<S::Components>, &mut S::Components, &mut S::Services),
     {
-        self.modify_entity_with_modifer(entity, modifier)
+        self.modify_entity_with_modifier(entity, modifier)
     }
 
+    #[deprecated(note = "renamed to `modify_entity_with_modifier`")]
     pub fn modify_entity_with_modifer<M>(&mut self, entity: Entity, modifier: M)
+    where
+        M: EntityModifier<S::Components, S::Services>,
+    {
+        self.modify_entity_with_modifier(entity, modifier)
+    }
+
+    pub fn modify_entity_with_modifier<M>(&mut self, entity: Entity, modifier: M)
     where
         M: EntityModifier<S::Components, S::Services>,
     {
         let indexed = self.data.entities.indexed(entity);
+        let before = indexed.signature();
         modifier.modify(
             ModifyData(indexed),
             &mut self.data.components,
             &mut self.data.services,
         );
+        self.data.entities.record_modified(entity, before);
+        let indexed = self.data.entities.indexed(entity);
         self.systems.reactivated(
             EntityData(indexed),
             &self.data.components,
@@ -130,17 +281,132 @@ where
     }
 
     pub fn flush_queue(&mut self) {
+        self.data.commands.flush(
+            &mut self.data.entities,
+            &mut self.data.components,
+            &mut self.data.services,
+        );
         self.data.entities.flush_queue(
             &mut self.data.components,
             &mut self.data.services,
             &mut self.systems,
         );
+        self.publish_entity_events();
+    }
+
+    fn publish_entity_events(&mut self) {
+        let events = self.data.entities.drain_lifecycle();
+        self.data.entity_events.emit_all(events);
     }
 
     pub fn update(&mut self) {
+        self.data.clock.sample();
+        self.data.services.__swap_events();
+        self.data.entity_events.swap();
+        self.data.services.__begin_frame();
         self.flush_queue();
         self.systems.update(&mut self.data);
         self.flush_queue();
+        self.data.services.__end_frame();
+
+        self.data.tick += 1;
+        self.data.components.__set_tick(self.data.tick);
+    }
+
+    /// Copies the entities and components of the world. Queued commands and
+    /// events which haven't been flushed yet are not included.
+    pub fn snapshot(&self) -> Snapshot<S::Components>
+    where
+        S::Components: Clone,
+    {
+        Snapshot {
+            tick: self.data.tick,
+            entities: self.data.entities.snapshot(),
+            components: self.data.components.clone(),
+        }
+    }
+
+    /// Rewinds the world to a snapshot, discarding anything queued since. The
+    /// systems are told about every entity leaving and then about every
+    /// entity in the snapshot, which brings their watchers back in sync.
+    ///
+    /// Entity generations are rewound too, so replaying the same updates
+    /// hands out the same entities again. That also means a handle to an
+    /// entity created after the snapshot was taken can end up referring to a
+    /// different entity created after the restore, so drop such handles
+    /// when restoring.
+    pub fn restore(&mut self, snapshot: &Snapshot<S::Components>)
+    where
+        S::Components: Clone,
+    {
+        for entity in self.data.entities.iter_activated() {
+            self.systems
+                .deactivated(entity, &self.data.components, &mut self.data.services);
+            self.data.entity_events.emit(EntityEvent::Removed(**entity));
+        }
+
+        self.data.commands = Commands::new();
+        self.data.entities = snapshot.entities.snapshot();
+        self.data.components = snapshot.components.clone();
+        self.data.tick = snapshot.tick;
+
+        for entity in self.data.entities.iter() {
+            self.systems
+                .activated(entity, &self.data.components, &mut self.data.services);
+        }
+        self.record_all_created();
+    }
+
+    /// Writes out every entity and component, along with the tick. Queued
+    /// commands and events which haven't been flushed yet are not saved.
+    #[cfg(feature = "serde")]
+    pub fn save<Ser>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+    where
+        Ser: serde::Serializer,
+        S::Components: SerializeComponents,
+    {
+        use serde::Serialize;
+
+        let entities = self.data.entities.save();
+        SavedWorld::new(self.data.tick, entities, &self.data.components).serialize(serializer)
+    }
+
+    /// Replaces the contents of the world with a save, then activates every
+    /// loaded entity in the systems. The world is left untouched if the save
+    /// can't be read.
+    #[cfg(feature = "serde")]
+    pub fn load<'de, D>(&mut self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        S::Components: SerializeComponents,
+    {
+        use serde::de::{Deserialize, Error};
+
+        let loaded = LoadedWorld::<S::Components>::deserialize(deserializer)?;
+        let entities = EntityManager::load(loaded.entities).map_err(D::Error::custom)?;
+        let components = loaded.components.0;
+        if let Err(entity) = components.__check_entities(|e| entities.is_valid(e)) {
+            return Err(D::Error::custom(format!(
+                "a component was saved for {:?}, which is not a saved entity",
+                entity
+            )));
+        }
+
+        self.wipe();
+        self.data.entities = entities;
+        self.data.components = components;
+        self.data.tick = loaded.tick;
+        self.data.components.__set_tick(loaded.tick);
+
+        for entity in self.data.entities.iter() {
+            self.data.components.__sync_signature(entity.0);
+        }
+        for entity in self.data.entities.iter() {
+            self.systems
+                .activated(entity, &self.data.components, &mut self.data.services);
+        }
+        self.record_all_created();
+        Ok(())
     }
 
     /// Mass delete all entities and their data
@@ -150,9 +416,20 @@ where
         for entity in self.data.entities.iter() {
             self.systems
                 .deactivated(entity, &self.data.components, &mut self.data.services);
+            self.data.entity_events.emit(EntityEvent::Removed(**entity));
         }
 
         self.data.entities.clear();
         self.data.components.__wipe_all();
+        self.data.services.__wipe();
+    }
+
+    /// Publishes every entity as created, after they were all replaced.
+    fn record_all_created(&mut self) {
+        let entities = self.data.entities.iter().map(|e| **e).collect::<Vec<_>>();
+        for entity in entities {
+            self.data.entities.record_created(entity);
+        }
+        self.publish_entity_events();
     }
 }
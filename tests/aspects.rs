@@ -18,7 +18,7 @@ pub struct Services;
 
 #[derive(Debug, ComponentManager)]
 pub struct Components {
-    #[cold]
+    #[storage(cold)]
     pub foo: Comps<String>,
 
     #[hot]
@@ -26,7 +26,7 @@ pub struct Components {
     #[hot]
     pub baz: Comps<[f32; 3]>,
 
-    #[cold]
+    #[storage(cold)]
     pub qux: Comps<Vec<i32>>,
 }
 
@@ -101,7 +101,7 @@ fn interval_process(iv: &mut IVSystem, _: &mut DataHelper) {
     iv.booped = !iv.booped;
 }
 
-static ATOMIC_BOOP: std::sync::atomic::AtomicBool = std::sync::atomic::ATOMIC_BOOL_INIT;
+static ATOMIC_BOOP: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
 
 fn activated(_: &mut IVSystem, _: EntityData, _: &Components, _: &mut Services) {
     ATOMIC_BOOP.store(true, std::sync::atomic::Ordering::SeqCst);
@@ -110,8 +110,8 @@ fn activated(_: &mut IVSystem, _: EntityData, _: &Components, _: &mut Services)
 #[test]
 pub fn simulate() {
     let mut world = conniecs::World::<Systems>::new();
-    assert_eq!(world.systems.ivsystem.booped, false);
-    assert_eq!(ATOMIC_BOOP.load(std::sync::atomic::Ordering::SeqCst), false);
+    assert!(!world.systems.ivsystem.booped);
+    assert!(!ATOMIC_BOOP.load(std::sync::atomic::Ordering::SeqCst));
 
     let asdf = world.data.create_entity(|e, c, _| {
         // We need a foo!
@@ -133,8 +133,8 @@ pub fn simulate() {
     });
 
     world.update();
-    assert_eq!(world.systems.ivsystem.booped, false);
-    assert_eq!(ATOMIC_BOOP.load(std::sync::atomic::Ordering::SeqCst), true);
+    assert!(!world.systems.ivsystem.booped);
+    assert!(ATOMIC_BOOP.load(std::sync::atomic::Ordering::SeqCst));
 
     world.data.with_entity_data(asdf, |e, c, _| {
         assert_eq!(&c.foo[e], "asdfghjkl");
@@ -149,7 +149,7 @@ pub fn simulate() {
     });
 
     world.update();
-    assert_eq!(world.systems.ivsystem.booped, false);
+    assert!(!world.systems.ivsystem.booped);
 
     world.data.with_entity_data(asdf, |e, c, _| {
         assert_eq!(&c.foo[e], "asdfghjkl");
@@ -162,7 +162,7 @@ pub fn simulate() {
     world.data.remove_entity(bar);
 
     world.update();
-    assert_eq!(world.systems.ivsystem.booped, true);
+    assert!(world.systems.ivsystem.booped);
 
     world.data.with_entity_data(baz, |e, c, _| {
         assert_eq!(c.baz[e][0], 1.25);
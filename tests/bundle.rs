@@ -0,0 +1,97 @@
+use conniecs::entity::RemoveBundle;
+use conniecs::system::EntitySystem;
+use conniecs::{Bundle, ComponentList, ComponentManager, ServiceManager, System, SystemManager};
+
+type Comps<T> = ComponentList<Components, T>;
+type EntityIter<'a> = conniecs::EntityIter<'a, Components>;
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services {}
+
+#[derive(Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    pub position: Comps<(f32, f32)>,
+    #[hot]
+    pub velocity: Comps<(f32, f32)>,
+    #[storage(cold)]
+    pub name: Comps<String>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems {
+    moving: EntitySystem<Moving>,
+}
+
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(position, velocity))]
+#[process(process)]
+pub struct Moving;
+
+fn process(_: &mut Moving, _: EntityIter, _: &mut DataHelper) {}
+
+#[derive(Bundle)]
+pub struct Body {
+    pub position: (f32, f32),
+    #[component(velocity)]
+    pub speed: (f32, f32),
+}
+
+#[derive(Bundle)]
+#[components(Components)]
+pub struct Named {
+    pub name: String,
+}
+
+#[test]
+pub fn bundles_build_entities() {
+    let mut world = World::new();
+    let entity = world.data.create_entity_with_builder(Body {
+        position: (1.0, 2.0),
+        speed: (3.0, 4.0),
+    });
+    world
+        .data
+        .commands
+        .create_entity_with_builder(Named { name: "a".into() });
+    world.update();
+
+    assert_eq!(world.systems.moving.watcher.interested.len(), 1);
+    let (position, velocity) = world
+        .data
+        .with_entity_data(entity, |e, c, _| (c.position[e], c.velocity[e]))
+        .unwrap();
+    assert_eq!(position, (1.0, 2.0));
+    assert_eq!(velocity, (3.0, 4.0));
+    assert_eq!(world.data.components.name.len(), 1);
+}
+
+#[test]
+pub fn bundles_modify_and_remove() {
+    let mut world = World::new();
+    let entity = world
+        .data
+        .create_entity_with_builder(Named { name: "a".into() });
+    world.update();
+    assert!(world.systems.moving.watcher.interested.is_empty());
+
+    let body = Body {
+        position: (0.0, 0.0),
+        speed: (1.0, 0.0),
+    };
+    assert!(world.data.modify_entity_with_modifier(entity, body));
+    world.update();
+    assert_eq!(world.systems.moving.watcher.interested.len(), 1);
+
+    world
+        .data
+        .modify_entity_with_modifier(entity, RemoveBundle::<Body>::new());
+    world.update();
+    assert!(world.systems.moving.watcher.interested.is_empty());
+    assert_eq!(world.data.components.position.len(), 0);
+    assert_eq!(world.data.components.velocity.len(), 0);
+    assert_eq!(world.data.components.name.len(), 1);
+}
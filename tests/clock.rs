@@ -0,0 +1,123 @@
+use conniecs::clock::{Clock, ManualClock};
+use conniecs::system::{FixedSystem, IntervalSystem};
+use conniecs::{ComponentManager, ServiceManager, System, SystemManager};
+
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type World = conniecs::World<Systems>;
+
+const MS: u64 = 1_000_000;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services {
+    pub spawns: u32,
+    pub steps: u32,
+}
+
+#[derive(Debug, ComponentManager)]
+pub struct Components;
+
+#[derive(SystemManager)]
+pub struct Systems {
+    spawner: IntervalSystem<Spawner>,
+    physics: FixedSystem<Physics>,
+}
+
+#[derive(Default, System)]
+#[system_type(interval)]
+#[interval = "100ms"]
+#[process(spawn)]
+pub struct Spawner;
+
+fn spawn(_: &mut Spawner, data: &mut DataHelper) {
+    data.services.spawns += 1;
+}
+
+#[derive(Default, System)]
+#[system_type(fixed)]
+#[timestep = "10ms"]
+#[max_steps = 100]
+#[process(physics)]
+pub struct Physics;
+
+fn physics(_: &mut Physics, _: f64, data: &mut DataHelper) {
+    data.services.steps += 1;
+}
+
+fn manual_world() -> (World, ManualClock) {
+    let time = ManualClock::new();
+    let mut world = World::new();
+    world.data.clock.set_source(time.clone());
+    world.update();
+    (world, time)
+}
+
+#[test]
+pub fn manual_clock_drives_timed_systems() {
+    let (mut world, time) = manual_world();
+
+    time.advance(99 * MS);
+    world.update();
+    assert_eq!(world.data.services.spawns, 0);
+    assert_eq!(world.data.services.steps, 9);
+
+    time.advance(MS);
+    world.update();
+    assert_eq!(world.data.services.spawns, 1);
+    assert_eq!(world.data.services.steps, 10);
+
+    // Nothing happens while time stands still
+    world.update();
+    world.update();
+    assert_eq!(world.data.services.spawns, 1);
+    assert_eq!(world.data.services.steps, 10);
+}
+
+#[test]
+pub fn paused_time_is_skipped() {
+    let (mut world, time) = manual_world();
+
+    world.data.clock.pause();
+    assert!(world.data.clock.is_paused());
+    time.advance(500 * MS);
+    world.update();
+    assert_eq!(world.data.clock.now(), 0);
+    assert_eq!(world.data.services.steps, 0);
+
+    world.data.clock.resume();
+    time.advance(50 * MS);
+    world.update();
+    assert_eq!(world.data.clock.now(), 50 * MS);
+    assert_eq!(world.data.services.steps, 5);
+}
+
+#[test]
+pub fn scaled_time() {
+    let (mut world, time) = manual_world();
+
+    world.data.clock.set_scale(2.0);
+    time.advance(50 * MS);
+    world.update();
+    assert_eq!(world.data.clock.now(), 100 * MS);
+    assert_eq!(world.data.services.spawns, 1);
+    assert_eq!(world.data.services.steps, 10);
+}
+
+#[test]
+pub fn fractional_scale_keeps_remainder() {
+    let time = ManualClock::new();
+    let mut clock = Clock::with_source(time.clone());
+    clock.set_scale(0.25);
+    clock.sample();
+
+    for _ in 0..4 {
+        time.advance(1);
+        clock.sample();
+    }
+    assert_eq!(clock.now(), 1);
+}
+
+#[test]
+#[should_panic]
+pub fn negative_scale_panics() {
+    Clock::new().set_scale(-1.0);
+}
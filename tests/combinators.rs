@@ -0,0 +1,114 @@
+use conniecs::system::EntitySystem;
+use conniecs::{Aspect, ComponentList, ComponentManager, ServiceManager, System, SystemManager};
+
+type Comps<T> = ComponentList<Components, T>;
+type EntityIter<'a> = conniecs::EntityIter<'a, Components>;
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services;
+
+#[derive(Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    pub a: Comps<()>,
+    #[hot]
+    pub b: Comps<()>,
+    #[storage(cold)]
+    pub c: Comps<()>,
+    #[hot]
+    pub mask: Comps<u8>,
+}
+
+#[derive(Aspect)]
+#[aspect(any(a, b))]
+pub struct AOrB;
+
+#[derive(Aspect)]
+#[aspect(not(a))]
+pub struct NotA;
+
+#[derive(Aspect)]
+#[aspect(all(a, any(b, c)), not(b, c))]
+pub struct AWithBXorC;
+
+#[derive(Aspect)]
+#[aspect(any(all(a, b), all(c, none(a, b))))]
+pub struct ABOrOnlyC;
+
+#[derive(SystemManager)]
+pub struct Systems {
+    inline: EntitySystem<Inline>,
+}
+
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(any(b, c), none(a))]
+#[process(process)]
+pub struct Inline;
+
+fn process(_: &mut Inline, _: EntityIter, _: &mut DataHelper) {}
+
+fn matching(world: &World, aspect: Aspect<Components>) -> Vec<u8> {
+    let components = &world.data.components;
+    let mut masks: Vec<u8> = world
+        .entities()
+        .filter(aspect, components)
+        .map(|e| components.mask[e])
+        .collect();
+    masks.sort();
+    masks
+}
+
+#[test]
+pub fn combinators() {
+    // One entity for every combination of a, b and c
+    let mut world = World::new();
+    for mask in 0..8u8 {
+        world.data.create_entity(|e, c, _| {
+            c.mask.add(e, mask);
+            if mask & 1 != 0 {
+                c.a.add(e, ());
+            }
+            if mask & 2 != 0 {
+                c.b.add(e, ());
+            }
+            if mask & 4 != 0 {
+                c.c.add(e, ());
+            }
+        });
+    }
+    world.update();
+
+    // Derived
+    assert_eq!(matching(&world, Aspect::new(AOrB)), vec![1, 2, 3, 5, 6, 7]);
+    assert_eq!(matching(&world, Aspect::new(NotA)), vec![0, 2, 4, 6]);
+    assert_eq!(matching(&world, Aspect::new(AWithBXorC)), vec![3, 5]);
+    assert_eq!(matching(&world, Aspect::new(ABOrOnlyC)), vec![3, 4, 7]);
+
+    // Inline
+    let components = &world.data.components;
+    let mut masks: Vec<u8> = world
+        .systems
+        .inline
+        .watcher
+        .iter()
+        .map(|e| components.mask[e])
+        .collect();
+    masks.sort();
+    assert_eq!(masks, vec![2, 4, 6]);
+
+    // Built at runtime
+    let aspect = Aspect::new(AOrB).and(!Aspect::new(NotA));
+    assert_eq!(matching(&world, aspect), vec![1, 3, 5, 7]);
+
+    let aspect = Aspect::new(AWithBXorC).or(Aspect::new(ABOrOnlyC));
+    assert_eq!(matching(&world, aspect), vec![3, 4, 5, 7]);
+
+    assert_eq!(matching(&world, !Aspect::all()), Vec::<u8>::new());
+    assert_eq!(
+        matching(&world, Aspect::none().or(Aspect::new(NotA))),
+        vec![0, 2, 4, 6]
+    );
+}
@@ -0,0 +1,157 @@
+use conniecs::system::EntitySystem;
+use conniecs::{ComponentList, ComponentManager, ServiceManager, System, SystemManager};
+
+type Comps<T> = ComponentList<Components, T>;
+type EntityIter<'a> = conniecs::EntityIter<'a, Components>;
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type EntityData<'a> = conniecs::EntityData<'a, Components>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services;
+
+#[derive(Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    pub health: Comps<i32>,
+    #[storage(cold)]
+    pub dead: Comps<()>,
+    #[storage(cold)]
+    pub corpse: Comps<()>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems {
+    reaper: EntitySystem<Reaper>,
+    undertaker: EntitySystem<Undertaker>,
+
+    #[passive]
+    counter: Counter,
+}
+
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(health), none(dead))]
+#[process(reap)]
+pub struct Reaper;
+
+fn reap(_: &mut Reaper, entities: EntityIter, data: &mut DataHelper) {
+    for entity in entities {
+        if data.components.health[entity] <= 0 {
+            data.commands.insert(**entity, |c| &mut c.dead, ());
+            data.commands.remove(**entity, |c| &mut c.health);
+            data.commands.create_entity(|e, c, _| {
+                c.corpse.add(e, ());
+            });
+        }
+    }
+}
+
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(corpse))]
+#[process(bury)]
+pub struct Undertaker;
+
+fn bury(_: &mut Undertaker, entities: EntityIter, data: &mut DataHelper) {
+    for entity in entities {
+        data.commands.remove_entity(**entity);
+    }
+}
+
+#[derive(Default, System)]
+#[activated(count_activated)]
+#[reactivated(count)]
+pub struct Counter {
+    activations: usize,
+    reactivations: usize,
+}
+
+fn count_activated(counter: &mut Counter, _: EntityData, _: &Components, _: &mut Services) {
+    counter.activations += 1;
+}
+
+fn count(counter: &mut Counter, _: EntityData, _: &Components, _: &mut Services) {
+    counter.reactivations += 1;
+}
+
+#[test]
+pub fn commands_apply_on_flush() {
+    let mut world = conniecs::World::<Systems>::new();
+    let alive = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 10);
+    });
+    let doomed = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 0);
+    });
+    world.update();
+
+    // The reaper queued two edits against `doomed`, which only count once
+    assert_eq!(world.systems.counter.reactivations, 1);
+    assert!(world.data.commands.is_empty());
+    assert_eq!(world.systems.reaper.watcher.interested.len(), 1);
+    assert_eq!(world.systems.undertaker.watcher.interested.len(), 1);
+
+    world.data.with_entity_data(doomed, |e, c, _| {
+        assert!(c.dead.has(e));
+        assert!(!c.health.has(e));
+    });
+    world.data.with_entity_data(alive, |e, c, _| {
+        assert!(!c.dead.has(e));
+        assert_eq!(c.health[e], 10);
+    });
+
+    // The corpse spawned last update gets buried by this one
+    world.update();
+    assert_eq!(world.entities().count(), 2);
+    assert_eq!(world.systems.undertaker.watcher.interested.len(), 0);
+}
+
+#[test]
+pub fn commands_against_removed_entities_are_ignored() {
+    let mut world = conniecs::World::<Systems>::new();
+    let entity = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 10);
+    });
+    world.update();
+
+    world.data.remove_entity(entity);
+    world.update();
+
+    world.data.commands.insert(entity, |c| &mut c.health, 5);
+    world.update();
+
+    assert_eq!(world.systems.counter.reactivations, 0);
+    assert_eq!(world.entities().count(), 0);
+}
+
+#[test]
+pub fn created_entities_are_only_activated() {
+    let mut world = conniecs::World::<Systems>::new();
+    let entity = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 10);
+    });
+    world.data.commands.insert(entity, |c| &mut c.health, 0);
+    world.flush_queue();
+
+    assert_eq!(world.systems.counter.activations, 1);
+    assert_eq!(world.systems.counter.reactivations, 0);
+    assert_eq!(world.systems.reaper.watcher.interested.len(), 1);
+}
+
+#[test]
+pub fn direct_and_queued_edits_reactivate_once() {
+    let mut world = conniecs::World::<Systems>::new();
+    let entity = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 10);
+    });
+    world.flush_queue();
+
+    world.data.modify_entity(entity, |e, c, _| {
+        c.health[e] = 5;
+    });
+    world.data.commands.insert(entity, |c| &mut c.health, 1);
+    world.flush_queue();
+
+    assert_eq!(world.systems.counter.activations, 1);
+    assert_eq!(world.systems.counter.reactivations, 1);
+}
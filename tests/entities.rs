@@ -0,0 +1,95 @@
+use conniecs::{ComponentList, ComponentManager, Entity, ServiceManager, SystemManager};
+
+type Comps<T> = ComponentList<Components, T>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services;
+
+#[derive(Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    pub name: Comps<&'static str>,
+    #[storage(cold)]
+    pub tag: Comps<()>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems;
+
+fn spawn(world: &mut World, name: &'static str) -> Entity {
+    world.data.create_entity(|e, c, _| {
+        c.name.add(e, name);
+    })
+}
+
+#[test]
+pub fn nil_is_never_valid() {
+    let mut world = World::new();
+    spawn(&mut world, "first");
+    world.update();
+
+    assert!(!world.data.is_valid(Entity::nil()));
+}
+
+#[test]
+pub fn stale_handles_are_rejected() {
+    let mut world = World::new();
+    let old = spawn(&mut world, "old");
+    world.update();
+    world.wipe();
+
+    let new = spawn(&mut world, "new");
+    world.update();
+
+    assert_eq!(old.index(), new.index());
+    assert!(old.generation() < new.generation());
+    assert_ne!(old.id(), new.id());
+
+    assert!(!world.data.is_valid(old));
+    assert!(world.data.with_entity_data(old, |_, _, _| ()).is_none());
+
+    let name = world.data.with_entity_data(new, |e, c, _| c.name[e]);
+    assert_eq!(name, Some("new"));
+}
+
+#[test]
+pub fn removed_entities_free_their_slot() {
+    let mut world = World::new();
+    let a = spawn(&mut world, "a");
+    let b = spawn(&mut world, "b");
+    world.modify_entity(a, |e, c, _| {
+        c.tag.set(e, ());
+    });
+    world.update();
+
+    assert!(world.data.remove_entity(a));
+    world.update();
+
+    assert!(!world.data.is_valid(a));
+    assert!(!world.data.remove_entity(a));
+    assert_eq!(world.entities().map(|e| **e).collect::<Vec<_>>(), vec![b]);
+
+    let c = spawn(&mut world, "c");
+    world.update();
+
+    assert_eq!(c.index(), a.index());
+    world.data.with_entity_data(c, |e, c, _| {
+        assert_eq!(c.name[e], "c");
+        assert!(!c.tag.has(e));
+    });
+}
+
+#[test]
+pub fn double_removal_is_harmless() {
+    let mut world = World::new();
+    let a = spawn(&mut world, "a");
+    world.update();
+
+    assert!(world.data.remove_entity(a));
+    assert!(world.data.remove_entity(a));
+    world.update();
+
+    assert!(!world.data.is_valid(a));
+    assert_eq!(world.entities().count(), 0);
+}
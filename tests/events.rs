@@ -0,0 +1,148 @@
+use conniecs::event::EventChannel;
+use conniecs::{ComponentManager, ReaderId, ServiceManager, System, SystemManager};
+
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type SplitData<'a> = conniecs::system::SplitData<'a, Components, Services>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit(pub u32);
+
+#[derive(Default, ServiceManager)]
+pub struct Services {
+    // Attributes the derive doesn't know about are left alone
+    #[allow(clippy::type_complexity)]
+    #[events]
+    pub hits: EventChannel<Hit>,
+    pub next: u32,
+    pub seen_later: Vec<u32>,
+}
+
+#[derive(Debug, ComponentManager)]
+pub struct Components;
+
+#[derive(SystemManager)]
+pub struct Systems {
+    early: Early,
+    emitter: Emitter,
+    late: Late,
+}
+
+#[derive(SystemManager)]
+#[parallel]
+pub struct ParallelSystems {
+    emitter: Emitter,
+    counter: Counter,
+}
+
+/// Runs before the emitter, so only sees hits from the last update
+#[derive(Default, System)]
+#[read_events(hits)]
+#[process(early)]
+pub struct Early {
+    hits: ReaderId<Hit>,
+    seen: Vec<u32>,
+}
+
+fn early(system: &mut Early, data: &mut DataHelper) {
+    let hits = data.services.hits.read(&mut system.hits);
+    system.seen.extend(hits.map(|hit| hit.0));
+}
+
+#[derive(Default, System)]
+#[process(emit)]
+pub struct Emitter;
+
+fn emit(_: &mut Emitter, data: &mut DataHelper) {
+    let next = data.services.next;
+    data.services.hits.emit_all(vec![Hit(next), Hit(next + 1)]);
+    data.services.next += 2;
+}
+
+#[derive(Default, System)]
+#[read_events(hits)]
+#[process(late)]
+pub struct Late {
+    hits: ReaderId<Hit>,
+}
+
+fn late(system: &mut Late, data: &mut DataHelper) {
+    let hits = data.services.hits.read(&mut system.hits);
+    let seen = hits.map(|hit| hit.0).collect::<Vec<_>>();
+    data.services.seen_later.extend(seen);
+}
+
+#[derive(Default, System)]
+#[read_events(hits)]
+#[reads()]
+#[process(count)]
+pub struct Counter {
+    hits: ReaderId<Hit>,
+    count: usize,
+}
+
+fn count(system: &mut Counter, data: &mut SplitData) {
+    let hits = data.service::<EventChannel<Hit>>("hits");
+    system.count += hits.read(&mut system.hits).count();
+}
+
+#[test]
+pub fn later_systems_see_events_in_the_same_update() {
+    let mut world = World::new();
+    world.update();
+    assert_eq!(world.data.services.seen_later, vec![0, 1]);
+    assert!(world.systems.early.seen.is_empty());
+
+    world.update();
+    assert_eq!(world.data.services.seen_later, vec![0, 1, 2, 3]);
+    assert_eq!(world.systems.early.seen, vec![0, 1]);
+
+    world.update();
+    assert_eq!(world.systems.early.seen, vec![0, 1, 2, 3]);
+}
+
+#[test]
+pub fn events_last_two_updates() {
+    let mut world = World::new();
+    world.update();
+    assert_eq!(world.data.services.hits.len(), 2);
+    world.update();
+    assert_eq!(world.data.services.hits.len(), 4);
+    world.update();
+    let buffered = world.data.services.hits.iter().map(|hit| hit.0);
+    assert_eq!(buffered.collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+}
+
+#[test]
+pub fn readers_only_see_events_once() {
+    let mut channel = EventChannel::new();
+    let mut reader = ReaderId::default();
+    channel.emit(Hit(1));
+    let mut late = channel.reader_at_end();
+
+    assert_eq!(channel.read(&mut reader).collect::<Vec<_>>(), vec![&Hit(1)]);
+    assert_eq!(channel.read(&mut reader).count(), 0);
+
+    channel.swap();
+    channel.emit(Hit(2));
+    assert_eq!(channel.read(&mut reader).collect::<Vec<_>>(), vec![&Hit(2)]);
+    assert_eq!(channel.read(&mut late).collect::<Vec<_>>(), vec![&Hit(2)]);
+
+    // Readers which fall behind miss the events that were dropped
+    let mut slow = ReaderId::default();
+    channel.swap();
+    channel.swap();
+    channel.emit(Hit(3));
+    assert_eq!(channel.read(&mut slow).collect::<Vec<_>>(), vec![&Hit(3)]);
+}
+
+#[test]
+pub fn parallel_systems_read_events() {
+    let mut world = conniecs::World::<ParallelSystems>::new();
+    world.update();
+    world.update();
+    assert_eq!(world.systems.counter.count, 4);
+
+    let (access, _) = conniecs::system::Process::split(&mut world.systems.counter).unwrap();
+    assert_eq!(access.reads_services, &["hits"]);
+}
@@ -0,0 +1,89 @@
+use conniecs::system::fixed::Timestep;
+use conniecs::system::FixedSystem;
+use conniecs::{ComponentManager, ServiceManager, System, SystemManager};
+
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services {
+    pub steps: Vec<f64>,
+    pub alphas: Vec<Option<f64>>,
+}
+
+#[derive(Debug, ComponentManager)]
+pub struct Components;
+
+#[derive(SystemManager)]
+pub struct Systems {
+    physics: FixedSystem<Physics>,
+    render: Render,
+}
+
+#[derive(Default, System)]
+#[system_type(fixed)]
+#[timestep = "1ns"]
+#[max_steps = 3]
+#[process(physics)]
+pub struct Physics;
+
+fn physics(_: &mut Physics, dt: f64, data: &mut DataHelper) {
+    data.services.steps.push(dt);
+}
+
+#[derive(Default, System)]
+#[process(render)]
+pub struct Render;
+
+fn render(_: &mut Render, data: &mut DataHelper) {
+    let alpha = data.interpolation::<Physics>();
+    data.services.alphas.push(alpha);
+}
+
+#[test]
+pub fn timestep_accumulates_whole_steps() {
+    let ms = 1_000_000;
+    let mut timestep = Timestep::new(10 * ms, 4);
+    assert_eq!(timestep.dt(), 0.01);
+
+    // The first advance only starts the clock
+    assert_eq!(timestep.advance(100 * ms), 0);
+    assert_eq!(timestep.alpha(), 0.0);
+
+    assert_eq!(timestep.advance(104 * ms), 0);
+    assert_eq!(timestep.alpha(), 0.4);
+
+    assert_eq!(timestep.advance(125 * ms), 2);
+    assert_eq!(timestep.alpha(), 0.5);
+
+    assert_eq!(timestep.advance(130 * ms), 1);
+    assert_eq!(timestep.alpha(), 0.0);
+}
+
+#[test]
+pub fn catch_up_is_limited() {
+    let ms = 1_000_000;
+    let mut timestep = Timestep::new(10 * ms, 4);
+    timestep.advance(0);
+
+    // A long stall only runs max_steps and drops the rest of the backlog
+    assert_eq!(timestep.advance(1003 * ms), 4);
+    assert_eq!(timestep.alpha(), 0.3);
+    assert_eq!(timestep.advance(1010 * ms), 1);
+}
+
+#[test]
+pub fn fixed_systems_step_and_publish_alpha() {
+    let mut world = World::new();
+    assert_eq!(world.data.interpolation::<Physics>(), None);
+
+    world.update();
+    assert!(world.data.services.steps.is_empty());
+    assert_eq!(world.data.services.alphas, vec![Some(0.0)]);
+
+    // More than 3ns always passes between updates
+    world.update();
+    assert_eq!(world.data.services.steps, vec![1e-9; 3]);
+    assert_eq!(world.data.services.alphas.len(), 2);
+    assert!(world.data.services.alphas[1].unwrap() < 1.0);
+}
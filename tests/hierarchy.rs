@@ -0,0 +1,180 @@
+use conniecs::system::EntitySystem;
+use conniecs::{ComponentList, ComponentManager, Entity, ServiceManager, System, SystemManager};
+
+type Comps<T> = ComponentList<Components, T>;
+type EntityIter<'a> = conniecs::EntityIter<'a, Components>;
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services {
+    pub removed: Vec<Entity>,
+}
+
+#[derive(Clone, Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    pub offset: Comps<f32>,
+    #[hot]
+    pub position: Comps<f32>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems {
+    transforms: EntitySystem<Transforms>,
+}
+
+/// Positions every entity relative to its parent
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(offset, position))]
+#[process(propagate)]
+#[deactivated(deactivated)]
+pub struct Transforms;
+
+fn propagate(_: &mut Transforms, _: EntityIter, data: &mut DataHelper) {
+    let order = data.hierarchy().depth_first().collect::<Vec<_>>();
+    for entity in order {
+        let base = data
+            .hierarchy()
+            .parent(entity)
+            .and_then(|parent| data.with_entity_data(parent, |e, c, _| c.position[e]))
+            .unwrap_or(0.0);
+        data.with_entity_data(entity, |e, c, _| c.position[e] = base + c.offset[e]);
+    }
+}
+
+fn deactivated(
+    _: &mut Transforms,
+    entity: conniecs::EntityData<Components>,
+    _: &Components,
+    services: &mut Services,
+) {
+    services.removed.push(**entity);
+}
+
+fn spawn(world: &mut World, offset: f32) -> Entity {
+    world.data.create_entity(|e, c, _| {
+        c.offset.add(e, offset);
+        c.position.add(e, 0.0);
+    })
+}
+
+fn position(world: &mut World, entity: Entity) -> Option<f32> {
+    world.data.with_entity_data(entity, |e, c, _| c.position[e])
+}
+
+/// root -> (a -> a1), b
+fn tree(world: &mut World) -> [Entity; 4] {
+    let root = spawn(world, 1.0);
+    let a = spawn(world, 10.0);
+    let b = spawn(world, 20.0);
+    let a1 = spawn(world, 100.0);
+    assert!(world.data.set_parent(a, root));
+    assert!(world.data.set_parent(b, root));
+    assert!(world.data.set_parent(a1, a));
+    [root, a, b, a1]
+}
+
+#[test]
+pub fn links_and_traversal() {
+    let mut world = World::new();
+    let [root, a, b, a1] = tree(&mut world);
+    let h = world.data.hierarchy();
+
+    assert_eq!(h.parent(a1), Some(a));
+    assert_eq!(h.parent(root), None);
+    assert_eq!(h.children(root), &[a, b]);
+    assert_eq!(h.ancestors(a1).collect::<Vec<_>>(), vec![a, root]);
+    assert_eq!(h.depth(a1), 2);
+    assert_eq!(h.descendants(root).collect::<Vec<_>>(), vec![a, a1, b]);
+    assert_eq!(h.depth_first().collect::<Vec<_>>(), vec![root, a, a1, b]);
+    assert_eq!(h.roots(), vec![root]);
+}
+
+#[test]
+pub fn cycles_are_refused() {
+    let mut world = World::new();
+    let [root, a, _, a1] = tree(&mut world);
+
+    assert!(!world.data.set_parent(root, a1));
+    assert!(!world.data.set_parent(a, a));
+    assert_eq!(world.data.hierarchy().parent(root), None);
+
+    // Reparenting moves the entity
+    assert!(world.data.set_parent(a1, root));
+    assert_eq!(world.data.hierarchy().children(a), &[] as &[Entity]);
+    assert_eq!(world.data.clear_parent(a1), Some(root));
+    assert_eq!(world.data.clear_parent(a1), None);
+}
+
+#[test]
+pub fn transforms_propagate_down_the_tree() {
+    let mut world = World::new();
+    let [root, a, b, a1] = tree(&mut world);
+    world.update();
+
+    assert_eq!(position(&mut world, root), Some(1.0));
+    assert_eq!(position(&mut world, a), Some(11.0));
+    assert_eq!(position(&mut world, b), Some(21.0));
+    assert_eq!(position(&mut world, a1), Some(111.0));
+}
+
+#[test]
+pub fn removal_cascades_to_the_subtree() {
+    let mut world = World::new();
+    let [root, a, b, a1] = tree(&mut world);
+    world.update();
+
+    world.data.remove_entity(a);
+    world.update();
+    assert_eq!(world.data.services.removed, vec![a, a1]);
+    assert!(!world.data.is_valid(a1));
+    assert_eq!(world.data.hierarchy().children(root), &[b]);
+
+    world.data.commands.remove_entity(root);
+    world.update();
+    assert_eq!(world.data.services.removed, vec![a, a1, root, b]);
+    assert_eq!(world.entities().count(), 0);
+    assert_eq!(world.data.hierarchy().depth_first().count(), 0);
+}
+
+#[test]
+pub fn snapshots_keep_the_hierarchy() {
+    let mut world = World::new();
+    let [root, a, b, a1] = tree(&mut world);
+    world.update();
+    let snapshot = world.snapshot();
+
+    world.data.remove_entity(root);
+    world.update();
+    world.restore(&snapshot);
+
+    let order = world.data.hierarchy().depth_first().collect::<Vec<_>>();
+    assert_eq!(order, vec![root, a, a1, b]);
+}
+
+#[test]
+pub fn entities_removed_before_they_are_activated() {
+    let mut world = World::new();
+    let parent = spawn(&mut world, 1.0);
+    world.update();
+
+    // The parent is already on its way out, so it can't take new children
+    world.data.remove_entity(parent);
+    let orphan = spawn(&mut world, 2.0);
+    assert!(!world.data.set_parent(orphan, parent));
+    world.update();
+    assert!(world.data.is_valid(orphan));
+
+    // A child which is removed with its parent is never activated
+    let parent = spawn(&mut world, 1.0);
+    world.update();
+    let child = spawn(&mut world, 2.0);
+    assert!(world.data.set_parent(child, parent));
+    world.data.remove_entity(parent);
+    world.update();
+    assert!(!world.data.is_valid(child));
+    assert_eq!(world.entities().count(), 1);
+    assert_eq!(world.systems.transforms.watcher.interested.len(), 1);
+}
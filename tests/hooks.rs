@@ -0,0 +1,117 @@
+use std::sync::{Arc, Mutex};
+
+use conniecs::services::ServiceHooks;
+use conniecs::{ComponentList, ComponentManager, ServiceManager, System, SystemManager};
+
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type World = conniecs::World<Systems>;
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+/// Writes every hook it sees to the shared log
+#[derive(Debug, Default)]
+pub struct Recorder {
+    pub name: &'static str,
+    pub log: Log,
+}
+
+impl Recorder {
+    fn push(&self, event: &str) {
+        let entry = format!("{} {}", self.name, event);
+        self.log.lock().unwrap().push(entry);
+    }
+}
+
+impl ServiceHooks for Recorder {
+    fn begin_frame(&mut self) {
+        self.push("begin");
+    }
+
+    fn end_frame(&mut self) {
+        self.push("end");
+    }
+
+    fn wipe(&mut self) {
+        self.push("wipe");
+    }
+}
+
+fn reset_input(input: &mut Recorder) {
+    input.push("reset");
+}
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services {
+    #[on_begin_frame]
+    #[on_wipe]
+    pub audio: Recorder,
+    #[on_begin_frame]
+    #[on_end_frame(reset_input)]
+    pub input: Recorder,
+    pub untouched: Recorder,
+}
+
+#[derive(Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    pub position: ComponentList<Components, f32>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems {
+    logger: Logger,
+}
+
+#[derive(Default, System)]
+#[process(process)]
+pub struct Logger;
+
+fn process(_: &mut Logger, data: &mut DataHelper) {
+    data.services.untouched.push("process");
+}
+
+fn world() -> (World, Log) {
+    let log = Log::default();
+    let recorder = |name| Recorder {
+        name,
+        log: log.clone(),
+    };
+    let services = Services {
+        audio: recorder("audio"),
+        input: recorder("input"),
+        untouched: recorder("untouched"),
+    };
+    (World::with_services(services), log)
+}
+
+fn take(log: &Log) -> Vec<String> {
+    log.lock().unwrap().drain(..).collect()
+}
+
+#[test]
+pub fn frame_hooks_surround_the_update() {
+    let (mut world, log) = world();
+    world.update();
+
+    assert_eq!(
+        take(&log),
+        [
+            "audio begin",
+            "input begin",
+            "untouched process",
+            "input reset",
+        ]
+    );
+}
+
+#[test]
+pub fn wipe_hooks_run_after_entities_are_gone() {
+    let (mut world, log) = world();
+    world.data.create_entity(|e, c, _| {
+        c.position.add(e, 0.0);
+    });
+    world.wipe();
+
+    assert_eq!(take(&log), ["audio wipe"]);
+    assert_eq!(world.entities().count(), 0);
+}
@@ -0,0 +1,130 @@
+use conniecs::system::EntitySystem;
+use conniecs::{ComponentList, ComponentManager, Entity, ServiceManager, System, SystemManager};
+
+type Comps<T> = ComponentList<Components, T>;
+type EntityIter<'a> = conniecs::EntityIter<'a, Components>;
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services;
+
+#[derive(Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    pub health: Comps<u32>,
+    #[storage(cold)]
+    pub poisoned: Comps<u32>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems {
+    sick: EntitySystem<Sick>,
+}
+
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(poisoned))]
+#[process(process)]
+pub struct Sick;
+
+fn process(_: &mut Sick, _: EntityIter, _: &mut DataHelper) {}
+
+#[test]
+pub fn lengths() {
+    let mut world = World::new();
+    assert!(world.data.components.poisoned.is_empty());
+
+    world.data.create_entity(|e, c, _| {
+        c.health.add(e, 10);
+        c.poisoned.add(e, 1);
+    });
+    world.data.create_entity(|e, c, _| {
+        c.health.add(e, 20);
+    });
+    world.update();
+
+    let c = &world.data.components;
+    assert_eq!(c.health.len(), 2);
+    assert_eq!(c.poisoned.len(), 1);
+    assert!(!c.poisoned.is_empty());
+    assert!(c.health.capacity() >= c.health.len());
+}
+
+#[test]
+pub fn iterate_hot_and_cold() {
+    let mut world = World::new();
+    let healthy = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 10);
+    });
+    let sick = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 20);
+        c.poisoned.add(e, 3);
+    });
+    world.update();
+    let c = &world.data.components;
+
+    let mut health: Vec<(Entity, u32)> = c.health.iter().map(|(e, &h)| (e, h)).collect();
+    health.sort_by_key(|&(_, h)| h);
+    assert_eq!(health, vec![(healthy, 10), (sick, 20)]);
+
+    let poisoned: Vec<(Entity, u32)> = (&c.poisoned).into_iter().map(|(e, &p)| (e, p)).collect();
+    assert_eq!(poisoned, vec![(sick, 3)]);
+}
+
+#[test]
+pub fn iterate_mutably() {
+    let mut world = World::new();
+    let sick = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 10);
+        c.poisoned.add(e, 3);
+    });
+    world.data.create_entity(|e, c, _| {
+        c.health.add(e, 20);
+    });
+    world.update();
+    let c = &mut world.data.components;
+
+    for (_, health) in &mut c.health {
+        *health += 1;
+    }
+    for (entity, poison) in c.poisoned.iter_mut() {
+        assert_eq!(entity, sick);
+        *poison *= 2;
+    }
+
+    let mut health: Vec<u32> = c.health.iter().map(|(_, &h)| h).collect();
+    health.sort();
+    assert_eq!(health, vec![11, 21]);
+    let poisoned: Vec<(Entity, u32)> = c.poisoned.iter().map(|(e, &p)| (e, p)).collect();
+    assert_eq!(poisoned, vec![(sick, 6)]);
+}
+
+#[test]
+pub fn drain_reactivates_entities() {
+    let mut world = World::new();
+    let first = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 10);
+        c.poisoned.add(e, 1);
+    });
+    let second = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 20);
+        c.poisoned.add(e, 2);
+    });
+    world.update();
+    assert_eq!(world.systems.sick.watcher.interested.len(), 2);
+
+    let mut drained = world.data.drain_components(|c| &mut c.poisoned);
+    drained.sort_by_key(|&(_, p)| p);
+    assert_eq!(drained, vec![(first, 1), (second, 2)]);
+    assert!(world.data.components.poisoned.is_empty());
+
+    world.update();
+    assert_eq!(world.systems.sick.watcher.interested.len(), 0);
+    for entity in world.entities() {
+        assert_eq!(
+            entity.signature(),
+            world.data.components.health.signature_bit()
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use conniecs::{ComponentList, ComponentManager, Join, ServiceManager, SystemManager};
+
+type Comps<T> = ComponentList<Components, T>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services;
+
+#[derive(Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    pub id: Comps<u32>,
+    #[hot]
+    pub position: Comps<f32>,
+    #[hot]
+    pub velocity: Comps<f32>,
+    #[storage(cold)]
+    pub drag: Comps<f32>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems;
+
+#[test]
+pub fn join_visits_entities_with_every_component() {
+    let mut world = World::new();
+    world.data.create_entity(|e, c, _| {
+        c.id.add(e, 0);
+        c.velocity.add(e, 1.0);
+        c.drag.add(e, 0.5);
+    });
+    world.data.create_entity(|e, c, _| {
+        c.id.add(e, 1);
+        c.velocity.add(e, 1.0);
+    });
+    world.data.create_entity(|e, c, _| {
+        c.id.add(e, 2);
+    });
+    world.update();
+    let c = &world.data.components;
+
+    let mut ids: Vec<u32> = (&c.id, &c.velocity).join().map(|(&id, _)| id).collect();
+    ids.sort();
+    assert_eq!(ids, vec![0, 1]);
+
+    let ids: Vec<u32> = (&c.drag, &c.id).join().map(|(_, &id)| id).collect();
+    assert_eq!(ids, vec![0]);
+
+    assert_eq!((&c.id,).join().count(), 3);
+    assert_eq!(c.id.join().count(), 3);
+}
+
+#[test]
+pub fn join_mutably() {
+    let mut world = World::new();
+    let dragged = world.data.create_entity(|e, c, _| {
+        c.position.add(e, 0.0);
+        c.velocity.add(e, 4.0);
+        c.drag.add(e, 0.5);
+    });
+    let moving = world.data.create_entity(|e, c, _| {
+        c.position.add(e, 0.0);
+        c.velocity.add(e, 4.0);
+    });
+    let still = world.data.create_entity(|e, c, _| {
+        c.position.add(e, 0.0);
+    });
+    world.update();
+    let c = &mut world.data.components;
+
+    for (pos, vel, drag) in (&mut c.position, &c.velocity, c.drag.maybe()).join() {
+        *pos += vel * drag.cloned().unwrap_or(1.0);
+    }
+
+    world.data.with_entity_data(dragged, |e, c, _| assert_eq!(c.position[e], 2.0));
+    world.data.with_entity_data(moving, |e, c, _| assert_eq!(c.position[e], 4.0));
+    world.data.with_entity_data(still, |e, c, _| assert_eq!(c.position[e], 0.0));
+}
+
+#[test]
+pub fn join_optional_mutably() {
+    let mut world = World::new();
+    world.data.create_entity(|e, c, _| {
+        c.velocity.add(e, 1.0);
+        c.drag.add(e, 0.5);
+    });
+    world.data.create_entity(|e, c, _| {
+        c.velocity.add(e, 1.0);
+    });
+    world.update();
+    let c = &mut world.data.components;
+
+    let mut seen = 0;
+    for (_, drag) in (&c.velocity, c.drag.maybe_mut()).join() {
+        seen += 1;
+        if let Some(drag) = drag {
+            *drag = 1.0;
+        }
+    }
+    assert_eq!(seen, 2);
+    assert!((&c.drag).join().all(|&drag| drag == 1.0));
+}
+
+#[test]
+#[should_panic]
+pub fn join_needs_a_required_list() {
+    let world = World::new();
+    let c = &world.data.components;
+    (c.id.maybe(), c.drag.maybe()).join();
+}
@@ -0,0 +1,111 @@
+use conniecs::entity::EntityEvent::{self, *};
+use conniecs::{ComponentList, ComponentManager, Entity, ReaderId, ServiceManager, SystemManager};
+
+type Comps<T> = ComponentList<Components, T>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services;
+
+#[derive(Clone, Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    pub position: Comps<f32>,
+    #[hot]
+    pub velocity: Comps<f32>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems {}
+
+fn read(world: &World, reader: &mut ReaderId<EntityEvent>) -> Vec<EntityEvent> {
+    world.data.entity_events().read(reader).copied().collect()
+}
+
+fn spawn(world: &mut World) -> Entity {
+    world.data.create_entity(|e, c, _| {
+        c.position.add(e, 0.0);
+    })
+}
+
+#[test]
+pub fn creation_and_removal_are_published() {
+    let mut world = World::new();
+    let mut reader = ReaderId::default();
+
+    let entity = spawn(&mut world);
+    assert!(read(&world, &mut reader).is_empty());
+
+    world.update();
+    assert_eq!(
+        read(&world, &mut reader),
+        vec![Created(entity), ComponentAdded(entity, "position")]
+    );
+
+    world.data.remove_entity(entity);
+    world.update();
+    assert_eq!(read(&world, &mut reader), vec![Removed(entity)]);
+}
+
+#[test]
+pub fn modifications_report_component_changes() {
+    let mut world = World::new();
+    let entity = spawn(&mut world);
+    world.update();
+    let mut reader = world.data.entity_events().reader_at_end();
+
+    world.data.modify_entity(entity, |e, c, _| {
+        c.position.remove(e);
+        c.velocity.set(e, 1.0);
+    });
+    world.data.commands.insert(entity, |c| &mut c.position, 2.0);
+    world.update();
+
+    assert_eq!(
+        read(&world, &mut reader),
+        vec![
+            Modified(entity),
+            ComponentRemoved(entity, "position"),
+            ComponentAdded(entity, "velocity"),
+            Modified(entity),
+            ComponentAdded(entity, "position"),
+        ]
+    );
+
+    world.data.drain_components(|c| &mut c.velocity);
+    world.update();
+    assert_eq!(
+        read(&world, &mut reader),
+        vec![ComponentRemoved(entity, "velocity")]
+    );
+}
+
+#[test]
+pub fn events_expire_after_two_updates() {
+    let mut world = World::new();
+    spawn(&mut world);
+    world.update();
+    assert_eq!(world.data.entity_events().len(), 2);
+    world.update();
+    assert_eq!(world.data.entity_events().len(), 2);
+    world.update();
+    assert!(world.data.entity_events().is_empty());
+}
+
+#[test]
+pub fn wiping_and_restoring_are_published() {
+    let mut world = World::new();
+    let entity = spawn(&mut world);
+    world.update();
+    let snapshot = world.snapshot();
+    let mut reader = world.data.entity_events().reader_at_end();
+
+    world.wipe();
+    assert_eq!(read(&world, &mut reader), vec![Removed(entity)]);
+
+    world.restore(&snapshot);
+    assert_eq!(
+        read(&world, &mut reader),
+        vec![Created(entity), ComponentAdded(entity, "position")]
+    );
+}
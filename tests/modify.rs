@@ -0,0 +1,95 @@
+use conniecs::system::{EntitySystem, InteractSystem};
+use conniecs::{ComponentList, ComponentManager, Entity, ServiceManager, System, SystemManager};
+
+type Comps<T> = ComponentList<Components, T>;
+type EntityIter<'a> = conniecs::EntityIter<'a, Components>;
+type DataHelper = conniecs::DataHelper<Components, Services>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services;
+
+#[derive(Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    pub health: Comps<i32>,
+    #[storage(cold)]
+    pub poisoned: Comps<i32>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems {
+    poisoner: Poisoner,
+    poison: EntitySystem<Poison>,
+    contagion: InteractSystem<Contagion>,
+}
+
+#[derive(Default, System)]
+#[process(poison_everyone)]
+pub struct Poisoner;
+
+fn poison_everyone(_: &mut Poisoner, data: &mut DataHelper) {
+    let mut targets: Vec<Entity> = vec![];
+    for entity in data.entities() {
+        if !data.components.poisoned.has(entity) {
+            targets.push(**entity);
+        }
+    }
+
+    for target in targets {
+        assert!(data.modify_entity(target, |e, c, _| {
+            c.poisoned.set(e, 1);
+        }));
+    }
+}
+
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(health, poisoned))]
+#[process(tick_poison)]
+pub struct Poison;
+
+fn tick_poison(_: &mut Poison, entities: EntityIter, data: &mut DataHelper) {
+    for entity in entities {
+        data.components.health[entity] -= data.components.poisoned[entity];
+    }
+}
+
+#[derive(Default, System)]
+#[system_type(interact)]
+#[aspect_a(all(poisoned))]
+#[aspect_b(all(health), none(poisoned))]
+pub struct Contagion;
+
+impl conniecs::system::interact::InteractProcess for Contagion {
+    fn process(&mut self, _: EntityIter, _: EntityIter, _: &mut DataHelper) {}
+}
+
+#[test]
+pub fn modifications_from_systems_are_watched() {
+    let mut world = conniecs::World::<Systems>::new();
+    let entity = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 10);
+    });
+    world.update();
+
+    assert_eq!(world.systems.poison.watcher.interested.len(), 1);
+    assert_eq!(world.systems.contagion.watcher_a.interested.len(), 1);
+    assert_eq!(world.systems.contagion.watcher_b.interested.len(), 0);
+
+    world.update();
+    world.data.with_entity_data(entity, |e, c, _| {
+        assert_eq!(c.health[e], 9);
+    });
+}
+
+#[test]
+pub fn modifying_a_removed_entity_fails() {
+    let mut world = conniecs::World::<Systems>::new();
+    let entity = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 10);
+    });
+    world.data.remove_entity(entity);
+    world.flush_queue();
+
+    assert!(!world.data.modify_entity(entity, |_, _, _| {}));
+}
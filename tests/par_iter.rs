@@ -0,0 +1,208 @@
+#![cfg(feature = "rayon")]
+
+use conniecs::par_iter::ParJoin;
+use conniecs::system::EntitySystem;
+use conniecs::tracking::Change;
+use conniecs::{ComponentList, ComponentManager, Entity, ServiceManager, System, SystemManager};
+use rayon::prelude::*;
+
+type Comps<T> = ComponentList<Components, T>;
+type EntityIter<'a> = conniecs::EntityIter<'a, Components>;
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services;
+
+#[derive(Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    #[tracked]
+    pub position: Comps<f32>,
+    #[hot]
+    pub velocity: Comps<f32>,
+    #[storage(flag)]
+    pub frozen: Comps<()>,
+    #[storage(dense)]
+    pub mass: Comps<f32>,
+    #[storage(cold)]
+    pub charge: Comps<f32>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems {
+    thaw: EntitySystem<Thaw>,
+}
+
+/// Pushes frozen bodies back towards the origin
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(position, frozen))]
+#[process(thaw)]
+pub struct Thaw;
+
+fn thaw(_: &mut Thaw, entities: EntityIter, data: &mut DataHelper) {
+    data.components
+        .position
+        .par_with_entities_mut(entities)
+        .for_each(|(_, position)| *position = 0.0);
+}
+
+#[test]
+pub fn entities_and_lists_iterate_in_parallel() {
+    let mut world = World::new();
+    let entities: Vec<Entity> = (0..1000)
+        .map(|i| {
+            world.data.create_entity(|e, c, _| {
+                c.position.add(e, 1.0);
+                if i % 2 == 0 {
+                    c.velocity.add(e, 1.0);
+                }
+                if i % 10 == 0 {
+                    c.frozen.add(e, ());
+                }
+            })
+        })
+        .collect();
+    world.update();
+
+    let mut watched: Vec<Entity> = world.systems.thaw.watcher.par_iter().map(|e| **e).collect();
+    watched.sort();
+    let frozen: Vec<Entity> = entities.iter().copied().step_by(10).collect();
+    assert_eq!(watched, frozen);
+
+    assert_eq!(world.entities().into_par_iter().count(), 1000);
+
+    let moving: f32 = world
+        .data
+        .components
+        .velocity
+        .par_iter()
+        .map(|(_, v)| v)
+        .sum();
+    assert_eq!(moving, 500.0);
+}
+
+#[test]
+pub fn mutable_access_is_disjoint() {
+    let mut world = World::new();
+    let entities: Vec<Entity> = (0..1000)
+        .map(|i| {
+            world.data.create_entity(|e, c, _| {
+                c.position.add(e, i as f32);
+                if i % 2 == 0 {
+                    c.velocity.add(e, 1.0);
+                }
+            })
+        })
+        .collect();
+    world.update();
+
+    let c = &mut world.data.components;
+    (&mut c.position, &c.velocity)
+        .par_join()
+        .for_each(|(position, velocity)| *position += velocity);
+    c.velocity.par_iter_mut().for_each(|(_, v)| *v *= 2.0);
+
+    for (i, &entity) in entities.iter().enumerate() {
+        let expected = if i % 2 == 0 { i as f32 + 1.0 } else { i as f32 };
+        let position = world
+            .data
+            .with_entity_data(entity, |e, c, _| c.position[e])
+            .unwrap();
+        assert_eq!(position, expected);
+    }
+    assert!(world
+        .data
+        .components
+        .velocity
+        .iter()
+        .all(|(_, &v)| v == 2.0));
+}
+
+#[test]
+pub fn systems_only_touch_their_entities() {
+    let mut world = World::new();
+    let entities: Vec<Entity> = (0..1000)
+        .map(|i| {
+            world.data.create_entity(|e, c, _| {
+                c.position.add(e, i as f32);
+                if i % 10 == 0 {
+                    c.frozen.add(e, ());
+                }
+            })
+        })
+        .collect();
+    world.update();
+    world.data.components.position.clear_changes();
+    world.update();
+
+    for (i, &entity) in entities.iter().enumerate() {
+        let position = world
+            .data
+            .with_entity_data(entity, |e, c, _| c.position[e])
+            .unwrap();
+        if i % 10 == 0 {
+            assert_eq!(position, 0.0);
+        } else {
+            assert_eq!(position, i as f32);
+        }
+    }
+
+    let mut changed: Vec<_> = world.data.components.position.changes_since(0).collect();
+    changed.sort();
+    let frozen: Vec<_> = entities
+        .iter()
+        .step_by(10)
+        .map(|&e| (e, Change::Modified))
+        .collect();
+    assert_eq!(changed, frozen);
+}
+
+#[test]
+pub fn every_storage_splits() {
+    let mut world = World::new();
+    let entities: Vec<Entity> = (0..1000)
+        .map(|i| {
+            world.data.create_entity(|e, c, _| {
+                if i % 10 == 0 {
+                    c.frozen.add(e, ());
+                }
+                if i % 3 == 0 {
+                    c.mass.add(e, 2.0);
+                }
+                if i % 5 == 0 {
+                    c.charge.add(e, -1.0);
+                }
+            })
+        })
+        .collect();
+    world.update();
+
+    let c = &mut world.data.components;
+    c.mass.par_iter_mut().for_each(|(_, m)| *m *= 3.0);
+    c.charge.par_iter_mut().for_each(|(_, q)| *q = -*q);
+    (&mut c.mass, &c.charge, c.frozen.maybe())
+        .par_join()
+        .for_each(|(mass, charge, frozen)| {
+            if frozen.is_none() {
+                *mass += charge;
+            }
+        });
+
+    assert_eq!(c.frozen.par_iter().count(), 100);
+    for (i, &entity) in entities.iter().enumerate() {
+        let (mass, charge) = world
+            .data
+            .with_entity_data(entity, |e, c, _| (c.mass.get(e), c.charge.get(e)))
+            .unwrap();
+        let expected = match (i % 3, i % 5, i % 10) {
+            (0, 0, 0) => Some(6.0),
+            (0, 0, _) => Some(7.0),
+            (0, _, _) => Some(6.0),
+            _ => None,
+        };
+        assert_eq!(mass, expected);
+        assert_eq!(charge, if i % 5 == 0 { Some(1.0) } else { None });
+    }
+}
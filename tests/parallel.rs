@@ -0,0 +1,386 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use conniecs::entity::EntityEvent;
+use conniecs::system::{Access, EntitySystem};
+use conniecs::{
+    ComponentList, ComponentManager, Entity, ReaderId, ServiceManager, System, SystemManager,
+};
+
+type Comps<T> = ComponentList<Components, T>;
+type EntityIter<'a> = conniecs::EntityIter<'a, Components>;
+type SplitData<'a> = conniecs::system::SplitData<'a, Components, Services>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services {
+    pub gravity: f32,
+    pub spawned: u32,
+    pub rendezvous: Rendezvous,
+}
+
+/// Counts the systems which turned up, and how many of them found another
+/// system there at the same time.
+#[derive(Debug, Default)]
+pub struct Rendezvous {
+    arrived: AtomicUsize,
+    met: AtomicUsize,
+}
+
+#[derive(Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    pub position: Comps<f32>,
+    #[hot]
+    pub velocity: Comps<f32>,
+    #[storage(cold)]
+    pub age: Comps<u32>,
+}
+
+#[derive(SystemManager)]
+#[parallel]
+pub struct Parallel {
+    #[before(movement)]
+    gravity: EntitySystem<Gravity>,
+    movement: EntitySystem<Movement>,
+    aging: Aging,
+    spawner: Spawner,
+}
+
+#[derive(SystemManager)]
+pub struct Serial {
+    gravity: EntitySystem<Gravity>,
+    movement: EntitySystem<Movement>,
+    aging: Aging,
+    spawner: Spawner,
+}
+
+#[derive(SystemManager)]
+pub struct Sneaky {
+    sneaky: Sneak,
+}
+
+#[derive(SystemManager)]
+pub struct Adopting {
+    adopter: Adopter,
+}
+
+#[derive(SystemManager)]
+pub struct Rewriting {
+    rewriter: Rewriter,
+}
+
+#[derive(SystemManager)]
+#[parallel]
+pub struct Meeting {
+    positions: MeetPositions,
+    ages: MeetAges,
+}
+
+#[derive(SystemManager)]
+#[parallel]
+pub struct PassiveOrder {
+    #[before(gate)]
+    positions: MeetPositions,
+    #[passive]
+    gate: Sneak,
+    #[after(gate)]
+    ages: MeetAges,
+}
+
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(velocity))]
+#[reads(services(gravity))]
+#[writes(velocity)]
+#[process(gravity)]
+pub struct Gravity;
+
+fn gravity(_: &mut Gravity, entities: EntityIter, data: &mut SplitData) {
+    let gravity = *data.service::<f32>("gravity");
+    let mut velocity = data.write::<f32>("velocity");
+    for entity in entities {
+        velocity[entity] -= gravity;
+    }
+}
+
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(position, velocity))]
+#[reads(velocity)]
+#[writes(position)]
+#[process(movement)]
+pub struct Movement;
+
+fn movement(_: &mut Movement, entities: EntityIter, data: &mut SplitData) {
+    let velocity = data.read::<f32>("velocity");
+    let mut position = data.write::<f32>("position");
+    for entity in entities {
+        position[entity] += velocity[entity];
+    }
+}
+
+#[derive(Default, System)]
+#[writes(age)]
+#[process(aging)]
+pub struct Aging;
+
+fn aging(_: &mut Aging, data: &mut SplitData) {
+    for (_, age) in data.write::<u32>("age").iter_mut() {
+        *age += 1;
+    }
+}
+
+/// Spawns a new entity every update through the view's commands
+#[derive(Default, System)]
+#[writes(services(spawned))]
+#[process(spawner)]
+pub struct Spawner;
+
+fn spawner(_: &mut Spawner, data: &mut SplitData) {
+    *data.service_mut::<u32>("spawned") += 1;
+    data.commands.create_entity(|e, c, _| {
+        c.age.add(e, 0);
+    });
+}
+
+#[derive(Default, System)]
+#[reads(position)]
+#[process(sneak)]
+pub struct Sneak;
+
+fn sneak(_: &mut Sneak, data: &mut SplitData) {
+    data.write::<f32>("position");
+}
+
+/// Parents every new entity to the first one it hears about
+#[derive(Default, System)]
+#[reads(age)]
+#[process(adopter)]
+pub struct Adopter {
+    reader: ReaderId<EntityEvent>,
+    root: Option<Entity>,
+}
+
+fn adopter(adopter: &mut Adopter, data: &mut SplitData) {
+    for event in data.entity_events().read(&mut adopter.reader) {
+        let entity = match *event {
+            EntityEvent::Created(entity) => entity,
+            _ => continue,
+        };
+        match adopter.root {
+            None => adopter.root = Some(entity),
+            Some(root) if data.hierarchy().parent(entity).is_none() => {
+                data.commands.set_parent(entity, root);
+            }
+            Some(_) => (),
+        }
+    }
+}
+
+#[derive(Default, System)]
+#[writes(position)]
+#[process(rewriter)]
+pub struct Rewriter;
+
+fn rewriter(_: &mut Rewriter, data: &mut SplitData) {
+    for (_, position) in data.write::<f32>("position").iter_mut() {
+        *position += 1.0;
+    }
+    for (_, position) in data.write::<f32>("position").iter_mut() {
+        *position *= 2.0;
+    }
+}
+
+#[derive(Default, System)]
+#[reads(services(rendezvous))]
+#[writes(position)]
+#[process(meet_positions)]
+pub struct MeetPositions;
+
+fn meet_positions(_: &mut MeetPositions, data: &mut SplitData) {
+    meet(data);
+}
+
+#[derive(Default, System)]
+#[reads(services(rendezvous))]
+#[writes(age)]
+#[process(meet_ages)]
+pub struct MeetAges;
+
+fn meet_ages(_: &mut MeetAges, data: &mut SplitData) {
+    meet(data);
+}
+
+/// Waits a little while for the other system to arrive
+fn meet(data: &SplitData) {
+    let rendezvous = data.service::<Rendezvous>("rendezvous");
+    rendezvous.arrived.fetch_add(1, Ordering::SeqCst);
+    let start = Instant::now();
+    while rendezvous.arrived.load(Ordering::SeqCst) < 2 {
+        if start.elapsed() > Duration::from_millis(500) {
+            return;
+        }
+        thread::yield_now();
+    }
+    rendezvous.met.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Updates the world on a pool with room for two systems at once
+fn update_on_two_threads<S>(world: &mut conniecs::World<S>)
+where
+    S: SystemManager<Components = Components, Services = Services> + Send,
+{
+    #[cfg(feature = "rayon")]
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap()
+        .install(|| world.update());
+    #[cfg(not(feature = "rayon"))]
+    world.update();
+}
+
+fn state<S>(world: &conniecs::World<S>) -> (Vec<f32>, Vec<u32>, u32)
+where
+    S: SystemManager<Components = Components, Services = Services>,
+{
+    let c = &world.data.components;
+    let mut positions: Vec<_> = c.position.iter().map(|(_, &p)| p).collect();
+    positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mut ages: Vec<_> = c.age.iter().map(|(_, &a)| a).collect();
+    ages.sort();
+    (positions, ages, world.data.services.spawned)
+}
+
+#[test]
+pub fn parallel_and_serial_managers_agree() {
+    let services = || Services {
+        gravity: 1.0,
+        ..Default::default()
+    };
+    let mut parallel = conniecs::World::<Parallel>::with_services(services());
+    let mut serial = conniecs::World::<Serial>::with_services(services());
+    for i in 0..10 {
+        parallel.data.create_entity(|e, c, _| {
+            c.position.add(e, i as f32);
+            c.velocity.add(e, 0.0);
+            c.age.add(e, 0);
+        });
+        serial.data.create_entity(|e, c, _| {
+            c.position.add(e, i as f32);
+            c.velocity.add(e, 0.0);
+            c.age.add(e, 0);
+        });
+    }
+    for _ in 0..5 {
+        parallel.update();
+        serial.update();
+    }
+
+    let (positions, ages, spawned) = state(&parallel);
+    assert_eq!((positions.clone(), ages.clone(), spawned), state(&serial));
+
+    // Gravity ran before movement every update: 1 + 2 + 3 + 4 + 5
+    assert_eq!(positions[0], -15.0);
+    assert_eq!(spawned, 5);
+    assert_eq!(ages.len(), 15);
+    assert_eq!(ages.iter().filter(|&&a| a == 5).count(), 10);
+}
+
+#[test]
+#[should_panic(expected = "didn't declare that it writes `position`")]
+pub fn undeclared_access_panics() {
+    let mut world = conniecs::World::<Sneaky>::with_services(Default::default());
+    world.update();
+}
+
+#[test]
+pub fn split_systems_see_events_and_edit_the_hierarchy() {
+    let mut world = conniecs::World::<Adopting>::with_services(Default::default());
+    for _ in 0..10 {
+        world.data.create_entity(|_, _, _| {});
+    }
+    world.update();
+
+    let root = world.systems.adopter.root.unwrap();
+    assert_eq!(world.data.hierarchy().children(root).len(), 9);
+    assert_eq!(world.data.hierarchy().roots(), [root]);
+}
+
+#[test]
+pub fn borrows_end_with_their_guards() {
+    let mut world = conniecs::World::<Rewriting>::with_services(Default::default());
+    world.data.create_entity(|e, c, _| {
+        c.position.add(e, 0.0);
+    });
+    world.data.create_entity(|e, c, _| {
+        c.position.add(e, 9.0);
+    });
+    world.update();
+
+    let (positions, _, _) = state(&world);
+    assert_eq!(positions, vec![2.0, 20.0]);
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+pub fn independent_systems_run_together() {
+    let mut world = conniecs::World::<Meeting>::with_services(Default::default());
+    update_on_two_threads(&mut world);
+
+    let rendezvous = &world.data.services.rendezvous;
+    assert_eq!(rendezvous.met.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+pub fn ordering_holds_through_passive_systems() {
+    let mut world = conniecs::World::<PassiveOrder>::with_services(Default::default());
+    update_on_two_threads(&mut world);
+
+    // `ages` had to wait for `positions`, so only the second one found
+    // anyone there
+    let rendezvous = &world.data.services.rendezvous;
+    assert_eq!(rendezvous.arrived.load(Ordering::SeqCst), 2);
+    assert_eq!(rendezvous.met.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+pub fn conflicts() {
+    let movement = Access {
+        reads: &["velocity"],
+        writes: &["position"],
+        ..Access::default()
+    };
+    let gravity = Access {
+        writes: &["velocity"],
+        reads_services: &["gravity"],
+        ..Access::default()
+    };
+    let render = Access {
+        reads: &["position", "velocity"],
+        ..Access::default()
+    };
+    let tuning = Access {
+        writes_services: &["gravity"],
+        ..Access::default()
+    };
+
+    assert!(movement.conflicts_with(&gravity));
+    assert!(movement.conflicts_with(&render));
+    assert!(!render.conflicts_with(&render));
+    assert!(gravity.conflicts_with(&tuning));
+    assert!(!movement.conflicts_with(&tuning));
+
+    let plugin = Access {
+        writes_resources: true,
+        ..Access::default()
+    };
+    let stats = Access {
+        reads_resources: true,
+        ..Access::default()
+    };
+    assert!(plugin.conflicts_with(&stats));
+    assert!(!stats.conflicts_with(&stats));
+    assert!(!plugin.conflicts_with(&movement));
+}
@@ -0,0 +1,147 @@
+use conniecs::entity::Prefab;
+use conniecs::system::EntitySystem;
+use conniecs::tracking::Change;
+use conniecs::{
+    BuildData, ComponentList, ComponentManager, Entity, ServiceManager, System, SystemManager,
+};
+
+type Comps<T> = ComponentList<Components, T>;
+type EntityIter<'a> = conniecs::EntityIter<'a, Components>;
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services {
+    pub spawned: u32,
+}
+
+/// Deliberately not `Clone`
+#[derive(Debug)]
+pub struct Handle(pub u32);
+
+#[derive(Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    #[tracked]
+    pub health: Comps<u32>,
+    #[storage(cold)]
+    pub name: Comps<String>,
+    #[storage(cold)]
+    pub handle: Comps<Handle>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems {
+    living: EntitySystem<Living>,
+}
+
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(health))]
+#[process(process)]
+pub struct Living;
+
+fn process(_: &mut Living, _: EntityIter, _: &mut DataHelper) {}
+
+/// A prefab described by data instead of code
+#[derive(Clone)]
+pub struct Monster {
+    pub name: &'static str,
+    pub health: u32,
+}
+
+impl conniecs::entity::EntityBuilder<Components, Services> for Monster {
+    fn build(self, e: BuildData<Components>, c: &mut Components, s: &mut Services) {
+        c.health.add(e, self.health);
+        c.name.add(e, self.name.to_string());
+        s.spawned += 1;
+    }
+}
+
+fn name(world: &mut World, entity: Entity) -> Option<String> {
+    world
+        .data
+        .with_entity_data(entity, |e, c, _| c.name.get(e))
+        .flatten()
+}
+
+#[test]
+pub fn prefabs_spawn_many_times() {
+    let mut world = World::new();
+    let orc = Prefab::from_builder(Monster {
+        name: "orc",
+        health: 30,
+    });
+    let rat = Prefab::new(|e, c: &mut Components, _: &mut Services| {
+        c.health.add(e, 1);
+    });
+
+    let orcs = world.data.instantiate(&orc, 3);
+    world.data.instantiate(&rat, 2);
+    world.data.commands.create_entity_with_builder(orc.clone());
+    world.update();
+
+    assert_eq!(world.data.services.spawned, 4);
+    assert_eq!(world.systems.living.watcher.interested.len(), 6);
+    assert_eq!(name(&mut world, orcs[2]), Some("orc".into()));
+}
+
+#[test]
+pub fn clones_copy_clone_components() {
+    let mut world = World::new();
+    let original = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 5);
+        c.name.add(e, "hero".into());
+        c.handle.add(e, Handle(1));
+    });
+    world.update();
+    world.data.components.health.clear_changes();
+
+    let copy = world.data.clone_entity(original).unwrap();
+    world.update();
+
+    assert_ne!(copy, original);
+    assert_eq!(name(&mut world, copy), Some("hero".into()));
+    let (health, has_handle) = world
+        .data
+        .with_entity_data(copy, |e, c, _| (c.health[e], c.handle.has(e)))
+        .unwrap();
+    assert_eq!(health, 5);
+    assert!(!has_handle);
+
+    let changes = world.data.components.health.changes_since(0);
+    assert_eq!(changes.collect::<Vec<_>>(), vec![(copy, Change::Added)]);
+    assert_eq!(world.systems.living.watcher.interested.len(), 2);
+
+    world.data.remove_entity(original);
+    world.update();
+    assert_eq!(world.data.clone_entity(original), None);
+}
+
+#[test]
+pub fn trees_are_cloned_with_their_links() {
+    let mut world = World::new();
+    let named = |name: &'static str| Prefab::from_builder(Monster { name, health: 1 });
+    let root = world.data.instantiate(&named("root"), 1)[0];
+    let a = world.data.instantiate(&named("a"), 1)[0];
+    let b = world.data.instantiate(&named("b"), 1)[0];
+    let a1 = world.data.instantiate(&named("a1"), 1)[0];
+    world.data.set_parent(a, root);
+    world.data.set_parent(b, root);
+    world.data.set_parent(a1, a);
+
+    let copy = world.data.clone_entity_tree(root).unwrap();
+    world.update();
+
+    assert_eq!(world.data.hierarchy().parent(copy), None);
+    let names = world
+        .data
+        .hierarchy()
+        .descendants(copy)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|e| name(&mut world, e).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["a", "a1", "b"]);
+    assert_eq!(world.entities().count(), 8);
+}
@@ -0,0 +1,130 @@
+use conniecs::{
+    ComponentList, ComponentManager, EventChannel, ServiceManager, System, SystemManager,
+};
+
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type SplitData<'a> = conniecs::system::SplitData<'a, Components, Services>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Score(pub u32);
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services {
+    pub score: Score,
+    pub best: Score,
+    pub name: String,
+    #[events]
+    pub hits: EventChannel<u32>,
+}
+
+#[derive(Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    pub position: ComponentList<Components, f32>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems {
+    plugin: Plugin,
+}
+
+#[derive(SystemManager)]
+#[parallel]
+pub struct SplitSystems {
+    plugin: SplitPlugin,
+    counter: Counter,
+}
+
+/// Keeps its state in the resources, as though it came from another crate
+#[derive(Default, System)]
+#[process(process)]
+pub struct Plugin;
+
+#[derive(Default)]
+pub struct PluginState {
+    pub updates: u32,
+}
+
+fn process(_: &mut Plugin, data: &mut DataHelper) {
+    data.resources.get_or_default::<PluginState>().updates += 1;
+    data.services.get_mut::<Score>().unwrap().0 += 1;
+}
+
+/// The same plugin, declaring its access
+#[derive(Default, System)]
+#[writes(resources)]
+#[process(split_process)]
+pub struct SplitPlugin;
+
+fn split_process(_: &mut SplitPlugin, data: &mut SplitData) {
+    data.resources_mut().get_or_default::<PluginState>().updates += 1;
+}
+
+/// Doesn't touch the resources, so can run next to the plugin
+#[derive(Default, System)]
+#[reads(position)]
+#[process(counter)]
+pub struct Counter;
+
+fn counter(_: &mut Counter, data: &mut SplitData) {
+    assert_eq!(data.read::<f32>("position").len(), 0);
+}
+
+#[test]
+pub fn services_are_found_by_type() {
+    let mut services = Services::default();
+    services.name.push_str("world");
+    services.best.0 = 7;
+
+    assert_eq!(services.get::<String>().unwrap(), "world");
+    assert_eq!(services.get::<Score>(), Some(&Score(0)));
+    assert!(services.get::<u64>().is_none());
+
+    services.get_mut::<EventChannel<u32>>().unwrap().emit(3);
+    assert_eq!(services.hits.len(), 1);
+}
+
+#[test]
+pub fn resources_hold_one_value_per_type() {
+    let mut world = World::new();
+    let resources = &mut world.data.resources;
+    assert!(resources.is_empty());
+
+    assert_eq!(resources.insert(Score(1)), None);
+    assert_eq!(resources.insert(Score(2)), Some(Score(1)));
+    resources.insert(5u32);
+    assert_eq!(resources.len(), 2);
+    assert_eq!(resources.get::<Score>(), Some(&Score(2)));
+
+    *resources.get_mut::<u32>().unwrap() += 1;
+    assert_eq!(resources.remove::<u32>(), Some(6));
+    assert!(!resources.contains::<u32>());
+}
+
+#[test]
+pub fn systems_use_services_and_resources() {
+    let mut world = World::new();
+    world.update();
+    world.update();
+
+    assert_eq!(world.data.services.score, Score(2));
+    assert_eq!(world.data.services.best, Score(0));
+    let state = world.data.resources.get::<PluginState>().unwrap();
+    assert_eq!(state.updates, 2);
+}
+
+#[test]
+pub fn split_systems_declare_resources() {
+    let mut world = conniecs::World::<SplitSystems>::new();
+    world.update();
+    world.update();
+
+    let state = world.data.resources.get::<PluginState>().unwrap();
+    assert_eq!(state.updates, 2);
+
+    let (access, _) = conniecs::system::Process::split(&mut world.systems.plugin).unwrap();
+    assert!(access.writes_resources);
+    let (counter, _) = conniecs::system::Process::split(&mut world.systems.counter).unwrap();
+    assert!(!access.conflicts_with(&counter));
+}
@@ -0,0 +1,84 @@
+use conniecs::{ComponentManager, ServiceManager, System, SystemManager};
+
+type DataHelper = conniecs::DataHelper<Components, Services>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services {
+    pub log: Vec<&'static str>,
+}
+
+#[derive(Debug, ComponentManager)]
+pub struct Components;
+
+macro_rules! logging_systems {
+    ($($name:ident),*) => {$(
+        #[derive(Debug, Default, System)]
+        #[process(log)]
+        pub struct $name;
+
+        impl Log for $name {
+            const NAME: &'static str = stringify!($name);
+        }
+    )*};
+}
+
+pub trait Log {
+    const NAME: &'static str;
+}
+
+fn log<S: Log>(_: &mut S, data: &mut DataHelper) {
+    data.services.log.push(S::NAME);
+}
+
+logging_systems!(Input, Physics, Collisions, Ai, Render, Audio, Ignored);
+
+#[derive(SystemManager)]
+#[stages(input, logic, render)]
+pub struct Systems {
+    #[stage(render)]
+    render: Render,
+    audio: Audio,
+    #[stage(logic)]
+    #[after(physics)]
+    collisions: Collisions,
+    #[before(physics)]
+    ai: Ai,
+    #[stage(logic)]
+    physics: Physics,
+    #[stage(input)]
+    #[before(audio)]
+    input: Input,
+    #[passive]
+    #[after(input)]
+    ignored: Ignored,
+}
+
+#[derive(SystemManager)]
+pub struct Unordered {
+    render: Render,
+    physics: Physics,
+    input: Input,
+}
+
+#[test]
+pub fn systems_run_in_dependency_order() {
+    let mut world = conniecs::World::<Systems>::new();
+    world.update();
+
+    assert_eq!(
+        world.data.services.log,
+        vec!["Ai", "Input", "Audio", "Physics", "Collisions", "Render"]
+    );
+}
+
+#[test]
+pub fn unconstrained_systems_keep_field_order() {
+    let mut world = conniecs::World::<Unordered>::new();
+    world.update();
+    world.update();
+
+    assert_eq!(
+        world.data.services.log,
+        vec!["Render", "Physics", "Input", "Render", "Physics", "Input"]
+    );
+}
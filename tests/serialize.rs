@@ -0,0 +1,190 @@
+#![cfg(feature = "serde")]
+
+use conniecs::system::EntitySystem;
+use conniecs::{ComponentList, ComponentManager, Entity, ServiceManager, System, SystemManager};
+use serde::{Deserialize, Serialize};
+
+type Comps<T> = ComponentList<Components, T>;
+type EntityIter<'a> = conniecs::EntityIter<'a, Components>;
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Name(String);
+
+#[derive(Debug, ComponentManager)]
+#[serialize]
+pub struct Components {
+    #[hot]
+    pub position: Comps<(f32, f32)>,
+    #[storage(cold)]
+    pub name: Comps<Name>,
+    #[storage(cold)]
+    pub target: Comps<Entity>,
+    #[hot]
+    #[transient]
+    pub cache: Comps<u32>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems {
+    named: EntitySystem<Named>,
+}
+
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(name))]
+#[process(process)]
+pub struct Named;
+
+fn process(_: &mut Named, _: EntityIter, _: &mut DataHelper) {}
+
+fn save(world: &World) -> String {
+    let mut json = Vec::new();
+    world
+        .save(&mut serde_json::Serializer::new(&mut json))
+        .unwrap();
+    String::from_utf8(json).unwrap()
+}
+
+#[test]
+pub fn save_and_load_round_trip() {
+    let mut world = World::new();
+    let a = world.data.create_entity(|e, c, _| {
+        c.position.add(e, (1.0, 2.0));
+        c.name.add(e, Name("a".into()));
+        c.cache.add(e, 7);
+    });
+    let removed = world.data.create_entity(|_, _, _| {});
+    let b = world.data.create_entity(|e, c, _| {
+        c.position.add(e, (3.0, 4.0));
+        c.target.add(e, a);
+    });
+    world.update();
+    world.data.remove_entity(removed);
+    world.update();
+    let json = save(&world);
+
+    let mut loaded = World::new();
+    loaded.data.create_entity(|e, c, _| {
+        c.name.add(e, Name("overwritten".into()));
+    });
+    loaded.update();
+    loaded
+        .load(&mut serde_json::Deserializer::from_str(&json))
+        .unwrap();
+
+    assert_eq!(loaded.data.tick(), world.data.tick());
+    assert!(loaded.data.is_valid(a));
+    assert!(!loaded.data.is_valid(removed));
+    assert!(loaded.data.is_valid(b));
+    assert_eq!(loaded.entities().count(), 2);
+
+    let c = &loaded.data.components;
+    assert_eq!(
+        c.name.iter().collect::<Vec<_>>(),
+        vec![(a, &Name("a".into()))]
+    );
+    assert_eq!(c.target.iter().collect::<Vec<_>>(), vec![(b, &a)]);
+    assert_eq!(c.position.len(), 2);
+    assert!(c.cache.is_empty());
+
+    // Watchers were rebuilt from the loaded entities
+    let watched: Vec<Entity> = loaded.systems.named.watcher.iter().map(|e| **e).collect();
+    assert_eq!(watched, vec![a]);
+
+    // The removed slot is reused with a fresh generation
+    let reused = loaded.data.create_entity(|_, _, _| {});
+    assert_eq!(reused.index(), removed.index());
+    assert!(reused.generation() > removed.generation());
+}
+
+#[test]
+pub fn loaded_signatures_match() {
+    let mut world = World::new();
+    let cached = world.data.create_entity(|e, c, _| {
+        c.position.add(e, (1.0, 2.0));
+        c.cache.add(e, 7);
+    });
+    world.data.create_entity(|e, c, _| {
+        c.name.add(e, Name("b".into()));
+    });
+    world.update();
+    let json = save(&world);
+
+    let mut loaded = World::new();
+    loaded
+        .load(&mut serde_json::Deserializer::from_str(&json))
+        .unwrap();
+
+    for entity in loaded.entities() {
+        let original = world.entities().find(|e| ***e == **entity).unwrap();
+        // Transient lists aren't saved, so they drop out of signatures
+        let transient = loaded.data.components.cache.signature_bit();
+        assert_eq!(entity.signature(), original.signature() & !transient);
+    }
+    assert!(loaded.data.is_valid(cached));
+}
+
+#[test]
+pub fn bad_saves_leave_the_world_alone() {
+    let mut world = World::new();
+    let entity = world.data.create_entity(|_, _, _| {});
+    world.update();
+    let json = r#"{
+        "tick": 5,
+        "entities": { "generations": [1], "entities": [{ "index": 0, "generation": 2 }] },
+        "components": {}
+    }"#;
+
+    let result = world.load(&mut serde_json::Deserializer::from_str(json));
+    assert!(result.is_err());
+    assert!(world.data.is_valid(entity));
+    assert_eq!(world.entities().count(), 1);
+}
+
+#[test]
+pub fn components_of_unknown_entities_are_refused() {
+    let mut world = World::new();
+    let entity = world.data.create_entity(|_, _, _| {});
+    world.update();
+    let stale = r#"{
+        "tick": 5,
+        "entities": { "generations": [2], "entities": [{ "index": 0, "generation": 2 }] },
+        "components": { "position": [[{ "index": 0, "generation": 1 }, [0.0, 0.0]]] }
+    }"#;
+    let missing = r#"{
+        "tick": 5,
+        "entities": { "generations": [1], "entities": [{ "index": 0, "generation": 1 }] },
+        "components": { "name": [[{ "index": 3, "generation": 1 }, "ghost"]] }
+    }"#;
+
+    for json in &[stale, missing] {
+        let result = world.load(&mut serde_json::Deserializer::from_str(json));
+        assert!(result.is_err());
+        assert!(world.data.is_valid(entity));
+        assert_eq!(world.entities().count(), 1);
+    }
+}
+
+#[test]
+pub fn hierarchy_is_saved() {
+    let mut world = World::new();
+    let a = world.data.create_entity(|_, _, _| {});
+    let b = world.data.create_entity(|_, _, _| {});
+    let c = world.data.create_entity(|_, _, _| {});
+    world.data.set_parent(c, a);
+    world.data.set_parent(b, a);
+    world.update();
+    let json = save(&world);
+
+    let mut loaded = World::new();
+    loaded
+        .load(&mut serde_json::Deserializer::from_str(&json))
+        .unwrap();
+    assert_eq!(loaded.data.hierarchy().children(a), &[c, b]);
+    assert_eq!(loaded.data.hierarchy().parent(b), Some(a));
+}
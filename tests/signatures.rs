@@ -0,0 +1,96 @@
+use conniecs::system::EntitySystem;
+use conniecs::{ComponentList, ComponentManager, Entity, ServiceManager, System, SystemManager};
+
+type Comps<T> = ComponentList<Components, T>;
+type EntityIter<'a> = conniecs::EntityIter<'a, Components>;
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services;
+
+#[derive(Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    pub position: Comps<(f32, f32)>,
+    #[hot]
+    pub velocity: Comps<(f32, f32)>,
+    #[storage(cold)]
+    pub frozen: Comps<()>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems {
+    movement: EntitySystem<Movement>,
+}
+
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(position, velocity), none(frozen))]
+#[process(process)]
+pub struct Movement;
+
+fn process(_: &mut Movement, _: EntityIter, _: &mut DataHelper) {}
+
+fn signature(world: &World, entity: Entity) -> u64 {
+    world
+        .entities()
+        .find(|e| ***e == entity)
+        .unwrap()
+        .signature()
+}
+
+#[test]
+pub fn lists_are_assigned_bits_in_order() {
+    let world = World::new();
+    let components = &world.data.components;
+    assert_eq!(components.position.signature_bit(), 1);
+    assert_eq!(components.velocity.signature_bit(), 2);
+    assert_eq!(components.frozen.signature_bit(), 4);
+}
+
+#[test]
+pub fn signatures_follow_components() {
+    let mut world = World::new();
+    let entity = world.data.create_entity(|e, c, _| {
+        c.position.add(e, (0.0, 0.0));
+        c.velocity.add(e, (1.0, 0.0));
+    });
+    world.update();
+    assert_eq!(signature(&world, entity), 0b011);
+    assert_eq!(world.systems.movement.watcher.interested.len(), 1);
+
+    world.modify_entity(entity, |e, c, _| {
+        c.frozen.set(e, ());
+        c.velocity.remove(e);
+    });
+    assert_eq!(signature(&world, entity), 0b101);
+    assert_eq!(world.systems.movement.watcher.interested.len(), 0);
+
+    world.modify_entity(entity, |e, c, _| {
+        c.frozen.remove(e);
+        c.velocity.set(e, (0.0, 1.0));
+    });
+    assert_eq!(signature(&world, entity), 0b011);
+
+    // The watcher's copy is kept in sync as well
+    let watched = world.systems.movement.watcher.iter().next().unwrap();
+    assert_eq!(watched.signature(), 0b011);
+}
+
+#[test]
+pub fn reused_slots_start_with_an_empty_signature() {
+    let mut world = World::new();
+    let entity = world.data.create_entity(|e, c, _| {
+        c.frozen.add(e, ());
+    });
+    world.update();
+    world.data.remove_entity(entity);
+    world.update();
+
+    let entity = world.data.create_entity(|e, c, _| {
+        c.position.add(e, (0.0, 0.0));
+    });
+    world.update();
+    assert_eq!(signature(&world, entity), 0b001);
+}
@@ -0,0 +1,191 @@
+use conniecs::system::{EntitySystem, InteractSystem};
+use conniecs::{ComponentList, ComponentManager, Entity, ServiceManager, System, SystemManager};
+
+type Comps<T> = ComponentList<Components, T>;
+type EntityIter<'a> = conniecs::EntityIter<'a, Components>;
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services;
+
+#[derive(Debug, Clone, ComponentManager)]
+pub struct Components {
+    #[hot]
+    pub health: Comps<i32>,
+    #[storage(cold)]
+    pub poisoned: Comps<i32>,
+    #[storage(flag)]
+    pub player: Comps<()>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems {
+    poison: EntitySystem<Poison>,
+    contagion: InteractSystem<Contagion>,
+}
+
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(health, poisoned))]
+#[process(tick_poison)]
+pub struct Poison;
+
+fn tick_poison(_: &mut Poison, entities: EntityIter, data: &mut DataHelper) {
+    for entity in entities {
+        data.components.health[entity] -= data.components.poisoned[entity];
+    }
+}
+
+#[derive(Default, System)]
+#[system_type(interact)]
+#[aspect_a(all(poisoned))]
+#[aspect_b(all(health), none(poisoned))]
+pub struct Contagion;
+
+impl conniecs::system::interact::InteractProcess for Contagion {
+    fn process(&mut self, _: EntityIter, _: EntityIter, _: &mut DataHelper) {}
+}
+
+fn watched(world: &World) -> (Vec<Entity>, Vec<Entity>, Vec<Entity>) {
+    let poison = world.systems.poison.watcher.iter().map(|e| **e).collect();
+    let a = world
+        .systems
+        .contagion
+        .watcher_a
+        .iter()
+        .map(|e| **e)
+        .collect();
+    let b = world
+        .systems
+        .contagion
+        .watcher_b
+        .iter()
+        .map(|e| **e)
+        .collect();
+    (poison, a, b)
+}
+
+fn health(world: &World) -> Vec<(Entity, i32)> {
+    let mut health: Vec<_> = world
+        .data
+        .components
+        .health
+        .iter()
+        .map(|(e, &h)| (e, h))
+        .collect();
+    health.sort();
+    health
+}
+
+fn signatures(world: &World) -> Vec<(Entity, u64)> {
+    world.entities().map(|e| (**e, e.signature())).collect()
+}
+
+#[test]
+pub fn restore_rewinds_components_and_entities() {
+    let mut world = World::new();
+    world.data.create_entity(|e, c, _| {
+        c.health.add(e, 10);
+        c.poisoned.add(e, 1);
+        c.player.add(e, ());
+    });
+    let doomed = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 10);
+    });
+    let healthy = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 10);
+    });
+    world.update();
+
+    let snapshot = world.snapshot();
+    assert_eq!(snapshot.tick(), world.data.tick());
+
+    let before_health = health(&world);
+    let before_watched = watched(&world);
+    let before_signatures = signatures(&world);
+
+    // Mispredict a few frames
+    world.data.remove_entity(doomed);
+    world.modify_entity(healthy, |e, c, _| {
+        c.poisoned.set(e, 5);
+    });
+    let spawned = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 1);
+    });
+    world.update();
+    world.update();
+    assert_ne!(health(&world), before_health);
+
+    world.restore(&snapshot);
+
+    assert_eq!(world.data.tick(), snapshot.tick());
+    assert_eq!(health(&world), before_health);
+    assert_eq!(watched(&world), before_watched);
+    assert!(world.data.is_valid(doomed));
+    assert!(!world.data.is_valid(spawned));
+    let poisoned = world
+        .data
+        .with_entity_data(healthy, |e, c, _| c.poisoned.has(e));
+    assert_eq!(poisoned, Some(false));
+    assert_eq!(world.data.components.player.len(), 1);
+
+    assert_eq!(signatures(&world), before_signatures);
+
+    // Allocation state was rewound too, so replaying gives the same handles
+    world.data.remove_entity(doomed);
+    let respawned = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 1);
+    });
+    assert_eq!(respawned, spawned);
+}
+
+#[test]
+pub fn restore_discards_queued_work() {
+    let mut world = World::new();
+    let removed = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 10);
+        c.poisoned.add(e, 1);
+    });
+    let commanded = world.data.create_entity(|e, c, _| {
+        c.health.add(e, 10);
+        c.poisoned.add(e, 1);
+    });
+    world.update();
+    let snapshot = world.snapshot();
+
+    world.data.create_entity(|e, c, _| {
+        c.health.add(e, 1);
+        c.poisoned.add(e, 1);
+    });
+    world.data.remove_entity(removed);
+    world.data.commands.remove_entity(commanded);
+
+    world.restore(&snapshot);
+    world.update();
+
+    assert_eq!(world.entities().count(), 2);
+    assert_eq!(world.systems.poison.watcher.interested.len(), 2);
+}
+
+#[test]
+pub fn snapshots_can_be_restored_repeatedly() {
+    let mut world = World::new();
+    world.data.create_entity(|e, c, _| {
+        c.health.add(e, 10);
+        c.poisoned.add(e, 1);
+    });
+    world.update();
+
+    let snapshot = world.snapshot();
+    let expected = {
+        world.update();
+        health(&world)
+    };
+
+    for _ in 0..3 {
+        world.restore(&snapshot);
+        world.update();
+        assert_eq!(health(&world), expected);
+    }
+}
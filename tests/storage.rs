@@ -0,0 +1,215 @@
+use conniecs::storage::VecMapStorage;
+use conniecs::{
+    ComponentList, ComponentManager, ComponentStorage, Entity, ServiceManager, SystemManager,
+};
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+
+type Comps<T> = ComponentList<Components, T>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Player;
+
+/// Keeps components ordered by entity index
+#[derive(Default)]
+pub struct Ordered<T> {
+    map: BTreeMap<usize, (Entity, T)>,
+}
+
+impl<T: 'static> ComponentStorage<T> for Ordered<T> {
+    fn insert(&mut self, entity: Entity, component: T) -> Option<T> {
+        let old = self.map.insert(entity.index(), (entity, component));
+        old.map(|(_, component)| component)
+    }
+
+    fn remove(&mut self, index: usize) -> Option<T> {
+        self.map.remove(&index).map(|(_, component)| component)
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        self.map.get(&index).map(|(_, component)| component)
+    }
+
+    fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.map.get_mut(&index).map(|(_, component)| component)
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (Entity, &'a T)> + 'a> {
+        Box::new(self.map.values().map(|(e, c)| (*e, c)))
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> Box<dyn Iterator<Item = (Entity, &'a mut T)> + 'a> {
+        Box::new(self.map.values_mut().map(|(e, c)| (*e, c)))
+    }
+
+    fn drain(&mut self) -> Vec<(Entity, T)> {
+        let map = std::mem::take(&mut self.map);
+        map.into_values().collect()
+    }
+}
+
+#[derive(Debug, ComponentManager)]
+pub struct Components {
+    #[storage(VecMap)]
+    pub id: Comps<u32>,
+    #[storage(dense)]
+    pub position: Comps<f32>,
+    #[storage(flag)]
+    pub player: Comps<Player>,
+    #[storage(Flag)]
+    pub counted: Comps<Counted>,
+    #[storage("Ordered")]
+    pub name: Comps<&'static str>,
+}
+
+/// A tag which counts how many of itself are alive
+#[derive(Debug)]
+pub struct Counted(());
+
+thread_local! {
+    static COUNTED: Cell<usize> = const { Cell::new(0) };
+}
+
+impl Counted {
+    fn new() -> Self {
+        COUNTED.with(|count| count.set(count.get() + 1));
+        Counted(())
+    }
+
+    fn alive() -> usize {
+        COUNTED.with(Cell::get)
+    }
+}
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        COUNTED.with(|count| count.set(count.get() - 1));
+    }
+}
+
+#[derive(SystemManager)]
+pub struct Systems;
+
+#[test]
+pub fn dense_storage_survives_removal() {
+    let mut world = World::new();
+    let entities: Vec<Entity> = (0..5)
+        .map(|i| {
+            world.data.create_entity(|e, c, _| {
+                c.id.add(e, i);
+                c.position.add(e, i as f32);
+            })
+        })
+        .collect();
+    world.update();
+
+    world.data.remove_entity(entities[1]);
+    world.data.remove_entity(entities[3]);
+    world.update();
+
+    let c = &world.data.components;
+    assert_eq!(c.position.len(), 3);
+    for (entity, &position) in &c.position {
+        let id = c.id.iter().find(|&(e, _)| e == entity).map(|(_, &id)| id);
+        assert_eq!(id, Some(position as u32));
+    }
+
+    let mut positions: Vec<f32> = c.position.iter().map(|(_, &p)| p).collect();
+    positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(positions, vec![0.0, 2.0, 4.0]);
+}
+
+#[test]
+pub fn flag_storage() {
+    let mut world = World::new();
+    let player = world.data.create_entity(|e, c, _| {
+        c.player.add(e, Player);
+    });
+    let other = world.data.create_entity(|e, c, _| {
+        c.id.add(e, 1);
+    });
+    world.update();
+
+    let players: Vec<Entity> = world
+        .data
+        .components
+        .player
+        .iter()
+        .map(|(e, _)| e)
+        .collect();
+    assert_eq!(players, vec![player]);
+
+    world.modify_entity(player, |e, c, _| {
+        assert_eq!(c.player.remove(e), Some(Player));
+        assert_eq!(c.player.remove(e), None);
+    });
+    world.modify_entity(other, |e, c, _| {
+        assert!(!c.player.has(e));
+        assert_eq!(c.player.set(e, Player), None);
+        assert_eq!(c.player.get(e), Some(Player));
+    });
+    assert_eq!(world.data.components.player.len(), 1);
+}
+
+#[test]
+pub fn flag_storage_drops_tags() {
+    let mut world = World::new();
+    let entities: Vec<Entity> = (0..4)
+        .map(|_| {
+            world.data.create_entity(|e, c, _| {
+                c.counted.add(e, Counted::new());
+            })
+        })
+        .collect();
+    world.update();
+    assert_eq!(Counted::alive(), 4);
+
+    world.data.remove_entity(entities[0]);
+    world.update();
+    assert_eq!(Counted::alive(), 3);
+
+    drop(world);
+    assert_eq!(Counted::alive(), 0);
+}
+
+#[test]
+pub fn custom_storage() {
+    let mut world = World::new();
+    let entities: Vec<Entity> = ["a", "b", "c"]
+        .iter()
+        .map(|&name| {
+            world.data.create_entity(|e, c, _| {
+                c.name.add(e, name);
+            })
+        })
+        .collect();
+    world.update();
+
+    world.data.remove_entity(entities[1]);
+    world.update();
+
+    let names: Vec<(Entity, &str)> = world
+        .data
+        .components
+        .name
+        .iter()
+        .map(|(e, &name)| (e, name))
+        .collect();
+    assert_eq!(names, vec![(entities[0], "a"), (entities[2], "c")]);
+
+    let list = ComponentList::<Components, u32>::with_storage(VecMapStorage::default());
+    assert!(list.is_empty());
+}
@@ -0,0 +1,130 @@
+use conniecs::system::{EntitySystem, ToggleSystem};
+use conniecs::{ComponentList, ComponentManager, ServiceManager, System, SystemManager};
+
+type Comps<T> = ComponentList<Components, T>;
+type EntityIter<'a> = conniecs::EntityIter<'a, Components>;
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services {
+    pub moved: usize,
+    pub ticks: u32,
+}
+
+#[derive(Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    pub position: Comps<f32>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems {
+    movement: ToggleSystem<EntitySystem<Movement>>,
+    counter: Counter,
+}
+
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(position))]
+#[process(movement)]
+pub struct Movement;
+
+fn movement(_: &mut Movement, entities: EntityIter, data: &mut DataHelper) {
+    data.services.moved += entities.count();
+}
+
+#[derive(Default, System)]
+#[process(count)]
+pub struct Counter;
+
+fn count(_: &mut Counter, data: &mut DataHelper) {
+    data.services.ticks += 1;
+}
+
+fn spawn(world: &mut World, n: usize) {
+    for _ in 0..n {
+        world.data.create_entity(|e, c, _| {
+            c.position.add(e, 0.0);
+        });
+    }
+}
+
+#[test]
+pub fn disabled_systems_are_not_processed() {
+    let mut world = World::new();
+    spawn(&mut world, 2);
+    world.update();
+    assert_eq!(world.data.services.moved, 2);
+
+    world.systems.movement.disable();
+    world.update();
+    assert_eq!(world.data.services.moved, 2);
+    assert_eq!(world.data.services.ticks, 2);
+
+    world.systems.movement.enable();
+    world.update();
+    assert_eq!(world.data.services.moved, 4);
+}
+
+#[test]
+pub fn systems_are_toggled_by_name() {
+    let mut world = World::new();
+    assert!(world.systems.set_enabled("movement", false));
+    assert!(!world.systems.movement.is_enabled());
+    assert!(world.systems.set_enabled("movement", true));
+    assert!(world.systems.movement.is_enabled());
+
+    // Only toggle systems can be switched off
+    assert!(!world.systems.set_enabled("counter", false));
+    assert!(!world.systems.set_enabled("missing", false));
+}
+
+#[test]
+pub fn watchers_stay_in_sync_while_disabled() {
+    let mut world = World::new();
+    world.systems.movement.disable();
+    spawn(&mut world, 3);
+    world.update();
+    assert_eq!(world.systems.movement.watcher.interested.len(), 3);
+
+    world.systems.movement.enable();
+    world.update();
+    assert_eq!(world.data.services.moved, 3);
+}
+
+#[test]
+pub fn unwatched_systems_need_a_refresh() {
+    let mut world = World::new();
+    world.systems.movement.watch_while_disabled = false;
+    world.systems.movement.disable();
+    spawn(&mut world, 3);
+    world.update();
+    assert_eq!(world.systems.movement.watcher.interested.len(), 0);
+
+    world.systems.movement.enable();
+    world.refresh();
+    world.update();
+    assert_eq!(world.data.services.moved, 3);
+}
+
+#[test]
+pub fn unwatched_systems_still_hear_removals() {
+    let mut world = World::new();
+    world.systems.movement.watch_while_disabled = false;
+    let entity = world.data.create_entity(|e, c, _| {
+        c.position.add(e, 0.0);
+    });
+    world.update();
+    assert_eq!(world.systems.movement.watcher.interested.len(), 1);
+
+    world.systems.movement.disable();
+    world.data.remove_entity(entity);
+    world.update();
+    world.systems.movement.enable();
+    world.refresh();
+    world.update();
+
+    assert!(world.systems.movement.watcher.interested.is_empty());
+    assert_eq!(world.data.services.moved, 1);
+}
@@ -0,0 +1,296 @@
+use conniecs::system::EntitySystem;
+use conniecs::tracking::Change;
+use conniecs::{
+    ComponentList, ComponentManager, Entity, Join, ServiceManager, System, SystemManager,
+};
+
+type Comps<T> = ComponentList<Components, T>;
+type EntityIter<'a> = conniecs::EntityIter<'a, Components>;
+type DataHelper = conniecs::DataHelper<Components, Services>;
+type World = conniecs::World<Systems>;
+
+#[derive(Debug, Default, ServiceManager)]
+pub struct Services;
+
+#[derive(Debug, ComponentManager)]
+pub struct Components {
+    #[hot]
+    #[tracked]
+    pub transform: Comps<(f32, f32)>,
+    #[storage(cold)]
+    pub velocity: Comps<(f32, f32)>,
+}
+
+#[derive(SystemManager)]
+pub struct Systems {
+    movement: EntitySystem<Movement>,
+    observer: EntitySystem<Observer>,
+}
+
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(transform, velocity))]
+#[process(movement)]
+pub struct Movement;
+
+fn movement(_: &mut Movement, entities: EntityIter, data: &mut DataHelper) {
+    for e in entities {
+        let (dx, dy) = data.components.velocity[e];
+        if dx != 0.0 || dy != 0.0 {
+            let transform = &mut data.components.transform[e];
+            transform.0 += dx;
+            transform.1 += dy;
+        }
+    }
+}
+
+/// Records the transforms which changed each update
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(transform))]
+#[process(observe)]
+pub struct Observer {
+    pub seen: Vec<Vec<(Entity, Change)>>,
+}
+
+fn observe(observer: &mut Observer, _: EntityIter, data: &mut DataHelper) {
+    let mut changes: Vec<_> = data
+        .components
+        .transform
+        .changes_since(data.tick())
+        .collect();
+    changes.sort();
+    observer.seen.push(changes);
+
+    let tick = data.tick();
+    data.components.transform.clear_changes_before(tick);
+}
+
+#[test]
+pub fn only_tracked_lists_are_tracked() {
+    let world = World::new();
+    assert!(world.data.components.transform.is_tracked());
+    assert!(!world.data.components.velocity.is_tracked());
+}
+
+#[test]
+#[should_panic]
+pub fn untracked_lists_have_no_changes() {
+    let world = World::new();
+    world.data.components.velocity.changes_since(0).count();
+}
+
+#[test]
+pub fn systems_see_changes_from_this_tick() {
+    let mut world = World::new();
+    assert_eq!(world.data.tick(), 0);
+
+    let moving = world.data.create_entity(|e, c, _| {
+        c.transform.add(e, (0.0, 0.0));
+        c.velocity.add(e, (1.0, 0.0));
+    });
+    let still = world.data.create_entity(|e, c, _| {
+        c.transform.add(e, (0.0, 0.0));
+        c.velocity.add(e, (0.0, 0.0));
+    });
+
+    world.update();
+    assert_eq!(world.data.tick(), 1);
+    world.update();
+
+    let gone = world.data.create_entity(|e, c, _| {
+        c.transform.add(e, (5.0, 5.0));
+    });
+    world.update();
+    world.data.remove_entity(gone);
+    world.update();
+
+    let seen = &world.systems.observer.inner.seen;
+    assert_eq!(seen.len(), 4);
+
+    // The first update flushes the creation of both entities
+    assert!(seen[0].contains(&(moving, Change::Added)));
+    assert!(seen[0].contains(&(still, Change::Added)));
+    assert!(seen[0].contains(&(moving, Change::Modified)));
+    assert!(!seen[0].contains(&(still, Change::Modified)));
+
+    assert_eq!(seen[1], vec![(moving, Change::Modified)]);
+
+    let mut expected = vec![(moving, Change::Modified), (gone, Change::Added)];
+    expected.sort();
+    assert_eq!(seen[2], expected);
+
+    let mut expected = vec![(moving, Change::Modified), (gone, Change::Removed)];
+    expected.sort();
+    assert_eq!(seen[3], expected);
+}
+
+#[test]
+pub fn mutable_access_counts_as_modification() {
+    let mut world = World::new();
+    let entities: Vec<Entity> = (0..3)
+        .map(|i| {
+            world.data.create_entity(|e, c, _| {
+                c.transform.add(e, (i as f32, 0.0));
+                if i == 1 {
+                    c.velocity.add(e, (0.0, 0.0));
+                }
+            })
+        })
+        .collect();
+    world.update();
+    world.data.components.transform.clear_changes();
+
+    let c = &mut world.data.components;
+    for (transform, _) in (&mut c.transform, &c.velocity).join() {
+        transform.1 = 1.0;
+    }
+    let changes: Vec<_> = c.transform.changes_since(0).collect();
+    assert_eq!(changes, vec![(entities[1], Change::Modified)]);
+
+    c.transform.clear_changes();
+    for (_, transform) in c.transform.iter_mut() {
+        transform.1 = 2.0;
+    }
+    let mut changes: Vec<_> = c.transform.changes_since(0).collect();
+    changes.sort();
+    assert_eq!(
+        changes,
+        entities
+            .iter()
+            .map(|&e| (e, Change::Modified))
+            .collect::<Vec<_>>()
+    );
+
+    c.transform.clear_changes();
+    world.modify_entity(entities[2], |e, c, _| {
+        c.transform.set(e, (0.0, 0.0));
+        c.transform.borrow(e).unwrap().0 = 1.0;
+    });
+    world.modify_entity(entities[0], |e, c, _| {
+        c.transform.remove(e);
+    });
+
+    let mut changes: Vec<_> = world
+        .data
+        .components
+        .transform
+        .changes_since(world.data.tick())
+        .collect();
+    changes.sort();
+    assert_eq!(
+        changes,
+        vec![
+            (entities[0], Change::Removed),
+            (entities[2], Change::Modified)
+        ]
+    );
+}
+
+#[test]
+pub fn joins_only_track_what_they_yield() {
+    let mut world = World::new();
+    let entities: Vec<Entity> = (0..3)
+        .map(|i| {
+            world.data.create_entity(|e, c, _| {
+                c.transform.add(e, (0.0, 0.0));
+                if i == 1 {
+                    c.velocity.add(e, (1.0, 0.0));
+                }
+            })
+        })
+        .collect();
+    // Enough velocities elsewhere that the transforms drive the join
+    for _ in 0..5 {
+        world.data.create_entity(|e, c, _| {
+            c.velocity.add(e, (0.0, 0.0));
+        });
+    }
+    world.update();
+    world.data.components.transform.clear_changes();
+
+    let c = &mut world.data.components;
+    assert_eq!((&mut c.transform, &c.velocity).join().count(), 1);
+    let changes: Vec<_> = c.transform.changes_since(0).collect();
+    assert_eq!(changes, vec![(entities[1], Change::Modified)]);
+}
+
+#[derive(SystemManager)]
+pub struct EarlySystems {
+    early: EntitySystem<EarlyObserver>,
+    movement: EntitySystem<Movement>,
+}
+
+/// Runs before `Movement`, so only sees its changes an update late
+#[derive(Default, System)]
+#[system_type(entity)]
+#[aspect(all(transform))]
+#[process(observe_early)]
+pub struct EarlyObserver {
+    pub current: Vec<Vec<Entity>>,
+    pub previous: Vec<Vec<Entity>>,
+}
+
+fn observe_early(observer: &mut EarlyObserver, _: EntityIter, data: &mut DataHelper) {
+    let modified = |since| {
+        data.components
+            .transform
+            .changes_since(since)
+            .filter(|&(_, change)| change == Change::Modified)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>()
+    };
+    observer.current.push(modified(data.tick()));
+    observer
+        .previous
+        .push(modified(data.tick().saturating_sub(1)));
+}
+
+#[test]
+pub fn readers_before_writers_see_changes_an_update_late() {
+    let mut world = conniecs::World::<EarlySystems>::new();
+    let moving = world.data.create_entity(|e, c, _| {
+        c.transform.add(e, (0.0, 0.0));
+        c.velocity.add(e, (1.0, 0.0));
+    });
+    world.update();
+    world.update();
+
+    let observer = &world.systems.early.inner;
+    assert_eq!(observer.current, vec![vec![], vec![]]);
+    assert_eq!(observer.previous, vec![vec![], vec![moving]]);
+}
+
+#[test]
+pub fn changes_come_out_in_the_order_they_were_made() {
+    let mut world = World::new();
+    let entity = world.data.create_entity(|e, c, _| {
+        c.transform.add(e, (0.0, 0.0));
+    });
+    world.update();
+    world.data.components.transform.clear_changes();
+
+    world.modify_entity(entity, |e, c, _| {
+        c.transform.remove(e);
+        c.transform.set(e, (1.0, 1.0));
+    });
+    let changes: Vec<_> = world.data.components.transform.changes_since(0).collect();
+    assert_eq!(
+        changes,
+        vec![(entity, Change::Removed), (entity, Change::Added)]
+    );
+
+    world.modify_entity(entity, |e, c, _| {
+        c.transform.borrow(e).unwrap().0 = 2.0;
+        c.transform.remove(e);
+    });
+    let changes: Vec<_> = world.data.components.transform.changes_since(0).collect();
+    assert_eq!(
+        changes,
+        vec![
+            (entity, Change::Added),
+            (entity, Change::Modified),
+            (entity, Change::Removed)
+        ]
+    );
+}